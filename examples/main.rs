@@ -25,6 +25,7 @@ fn print_delta(identity: f32, encoded: f32, codec: Codec, quality: Quality, timi
     }
 }
 
+#[allow(clippy::needless_borrow)]
 fn run_test(encoding: Codec, quality: Quality) {
     if is_codec_enabled(encoding) {
         let (encode_elapsed, encoded) = {
@@ -64,10 +65,20 @@ fn run_tests(quality: Quality) {
     run_test(Brotli, quality);
     run_test(Zlib, quality);
     run_test(Zstd, quality);
+    run_test(Bzip2, quality);
     run_test(Lz4, quality);
+    run_test(Lz4Block, quality);
+    run_test(Snappy, quality);
+    run_test(SnappyRaw, quality);
     run_test(Xz, quality);
+    run_test(Lzma, quality);
+    run_test(Lzma2Raw, quality);
     run_test(BinCode, quality);
     run_test(Base58, quality);
+    run_test(Base58Check, quality);
+    run_test(Base64, quality);
+    run_test(Base32, quality);
+    run_test(Hex, quality);
 }
 
 fn main() {
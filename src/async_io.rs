@@ -0,0 +1,580 @@
+#[cfg(any(
+    feature = "brotli_support",
+    feature = "bzip2_support",
+    feature = "deflate_support",
+    feature = "gzip_support",
+    feature = "snappy_support",
+    feature = "xz_support",
+    feature = "zlib_support",
+    feature = "zstd_support",
+))]
+use crate::codecs;
+use crate::{Codec, Error, Quality};
+use std::io::{self, Write};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+const CHUNK_SIZE: usize = 8192;
+
+/// An in-memory sink shared between a synchronous codec and the async
+/// wrapper that forwards its output.
+#[derive(Clone, Default)]
+struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Wraps an `AsyncWrite` and encodes everything written to it with a codec.
+///
+/// `shutdown` must be called once all data has been written, otherwise the
+/// trailing bytes of the encoded stream may never reach the inner writer.
+/// Encoding happens on the calling task, so use `encode_async` for large
+/// buffers that are already in memory.
+pub struct AsyncEncoder<W> {
+    encoder: Option<crate::Encoder<SharedBuf>>,
+    sink: SharedBuf,
+    writer: W,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncEncoder<W> {
+    /// Creates an encoder that writes data encoded with `codec` to `writer`.
+    pub fn new(writer: W, codec: Codec, quality: Quality) -> crate::Result<Self> {
+        let sink = SharedBuf::default();
+        Ok(AsyncEncoder {
+            encoder: Some(crate::Encoder::new(sink.clone(), codec, quality)?),
+            sink,
+            writer,
+        })
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes any encoded bytes waiting in the sink to the inner writer.
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut buf = self.sink.0.lock().unwrap();
+        while !buf.is_empty() {
+            let written = ready!(Pin::new(&mut self.writer).poll_write(cx, &buf))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            buf.drain(..written);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for AsyncEncoder<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_drain(cx))?;
+        match this.encoder.as_mut() {
+            Some(encoder) => Poll::Ready(encoder.write(buf)),
+            None => Poll::Ready(Err(io::Error::other("write after shutdown"))),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if let Some(encoder) = this.encoder.as_mut() {
+            encoder.flush()?;
+        }
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.writer).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if let Some(encoder) = this.encoder.take() {
+            encoder.finish()?;
+        }
+        ready!(this.poll_drain(cx))?;
+        Pin::new(&mut this.writer).poll_shutdown(cx)
+    }
+}
+
+/// Wraps an `AsyncRead` and decodes the data read from it with a codec.
+///
+/// Text encodings, `Lz4`, `Lz4Block`, `Lzma2Raw`, `SnappyRaw`, `BinCode` and
+/// custom codecs are decoded once the inner reader reaches the end of its
+/// input, and every other codec is decoded as the input arrives.
+pub struct AsyncDecoder<R> {
+    codec: Codec,
+    decoder: Option<WriteDecoder<SharedBuf>>,
+    sink: SharedBuf,
+    pos: usize,
+    chunk: Box<[u8]>,
+    reader: R,
+}
+
+impl<R: AsyncRead + Unpin> AsyncDecoder<R> {
+    /// Creates a decoder that reads data encoded with `codec` from `reader`.
+    pub fn new(reader: R, codec: Codec) -> crate::Result<Self> {
+        let sink = SharedBuf::default();
+        Ok(AsyncDecoder {
+            codec,
+            decoder: Some(WriteDecoder::new(sink.clone(), codec)?),
+            sink,
+            pos: 0,
+            chunk: vec![0; CHUNK_SIZE].into_boxed_slice(),
+            reader,
+        })
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for AsyncDecoder<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            {
+                let mut decoded = this.sink.0.lock().unwrap();
+                if this.pos < decoded.len() {
+                    let len = buf.remaining().min(decoded.len() - this.pos);
+                    buf.put_slice(&decoded[this.pos..this.pos + len]);
+                    this.pos += len;
+                    if this.pos == decoded.len() {
+                        decoded.clear();
+                        this.pos = 0;
+                    }
+                    return Poll::Ready(Ok(()));
+                }
+            }
+
+            let decoder = match this.decoder.as_mut() {
+                Some(decoder) => decoder,
+                None => return Poll::Ready(Ok(())),
+            };
+            let mut chunk = ReadBuf::new(&mut this.chunk);
+            ready!(Pin::new(&mut this.reader).poll_read(cx, &mut chunk))?;
+            let codec = this.codec;
+            let corrupt = |err| io::Error::from(Error::corrupt(codec, err));
+            if chunk.filled().is_empty() {
+                this.decoder.take().unwrap().finish().map_err(corrupt)?;
+            } else {
+                decoder.write_all(chunk.filled()).map_err(corrupt)?;
+            }
+        }
+    }
+}
+
+/// A decoder that is fed encoded data through `Write` and writes the decoded
+/// data to an inner writer.
+#[allow(clippy::large_enum_variant)]
+enum WriteDecoder<W: Write> {
+    Identity(W),
+
+    #[cfg(feature = "gzip_support")]
+    Gzip(flate2::write::MultiGzDecoder<W>),
+
+    #[cfg(feature = "deflate_support")]
+    Deflate(flate2::write::DeflateDecoder<W>),
+
+    #[cfg(feature = "zlib_support")]
+    Zlib(flate2::write::ZlibDecoder<W>),
+
+    #[cfg(feature = "zstd_support")]
+    Zstd(zstd::stream::zio::Writer<W, zstd::stream::raw::Decoder>),
+
+    #[cfg(feature = "brotli_support")]
+    Brotli(brotli::DecompressorWriter<W>),
+
+    #[cfg(feature = "bzip2_support")]
+    Bzip2(codecs::bzip2::WriteDecoder<W>),
+
+    #[cfg(feature = "snappy_support")]
+    Snappy(codecs::snappy::WriteDecoder<W>),
+
+    #[cfg(feature = "xz_support")]
+    Xz(xz2::write::XzDecoder<W>),
+
+    Buffered {
+        codec: Codec,
+        buffer: Vec<u8>,
+        writer: W,
+    },
+}
+
+impl<W: Write> WriteDecoder<W> {
+    fn new(writer: W, codec: Codec) -> crate::Result<Self> {
+        Ok(match codec {
+            Codec::Identity => WriteDecoder::Identity(writer),
+
+            #[cfg(feature = "gzip_support")]
+            Codec::Gzip => WriteDecoder::Gzip(codecs::gzip::write_decoder(writer)?),
+
+            #[cfg(feature = "deflate_support")]
+            Codec::Deflate => WriteDecoder::Deflate(codecs::deflate::write_decoder(writer)?),
+
+            #[cfg(feature = "zlib_support")]
+            Codec::Zlib => WriteDecoder::Zlib(codecs::zlib::write_decoder(writer)?),
+
+            #[cfg(feature = "zstd_support")]
+            Codec::Zstd => WriteDecoder::Zstd(codecs::zstd::write_decoder(writer)?),
+
+            #[cfg(feature = "brotli_support")]
+            Codec::Brotli => WriteDecoder::Brotli(codecs::brotli::write_decoder(writer)?),
+
+            #[cfg(feature = "xz_support")]
+            Codec::Xz => WriteDecoder::Xz(codecs::xz::write_decoder(writer)?),
+
+            #[cfg(feature = "xz_support")]
+            Codec::Lzma => WriteDecoder::Xz(codecs::lzma::write_decoder(writer)?),
+
+            #[cfg(feature = "xz_support")]
+            Codec::Lzma2Raw => WriteDecoder::Buffered {
+                codec,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            #[cfg(feature = "bzip2_support")]
+            Codec::Bzip2 => WriteDecoder::Bzip2(codecs::bzip2::write_decoder(writer)?),
+
+            #[cfg(feature = "lz4_support")]
+            Codec::Lz4 | Codec::Lz4Block => WriteDecoder::Buffered {
+                codec,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            #[cfg(feature = "snappy_support")]
+            Codec::Snappy => WriteDecoder::Snappy(codecs::snappy::write_decoder(writer)?),
+
+            #[cfg(feature = "snappy_support")]
+            Codec::SnappyRaw => WriteDecoder::Buffered {
+                codec,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            #[cfg(feature = "bincode_support")]
+            Codec::BinCode => WriteDecoder::Buffered {
+                codec,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            #[cfg(feature = "base58_support")]
+            Codec::Base58
+            | Codec::Base58Ripple
+            | Codec::Base58Flickr
+            | Codec::Base58Check
+            | Codec::Base58CheckRipple
+            | Codec::Base58CheckFlickr => WriteDecoder::Buffered {
+                codec,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            #[cfg(feature = "base64_support")]
+            Codec::Base64 | Codec::Base64Url | Codec::Base64NoPad | Codec::Base64UrlNoPad => {
+                WriteDecoder::Buffered {
+                    codec,
+                    buffer: Vec::new(),
+                    writer,
+                }
+            }
+
+            #[cfg(feature = "base32_support")]
+            Codec::Base32 | Codec::Base32Crockford => WriteDecoder::Buffered {
+                codec,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            #[cfg(feature = "hex_support")]
+            Codec::Hex => WriteDecoder::Buffered {
+                codec,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            Codec::Custom(_) => WriteDecoder::Buffered {
+                codec,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            disabled => return Err(Error::CodecDisabled(disabled)),
+        })
+    }
+
+    /// Decodes any remaining input, returning the inner writer.
+    fn finish(self) -> io::Result<W> {
+        match self {
+            WriteDecoder::Identity(writer) => Ok(writer),
+
+            #[cfg(feature = "gzip_support")]
+            WriteDecoder::Gzip(decoder) => decoder.finish(),
+
+            #[cfg(feature = "deflate_support")]
+            WriteDecoder::Deflate(decoder) => decoder.finish(),
+
+            #[cfg(feature = "zlib_support")]
+            WriteDecoder::Zlib(decoder) => decoder.finish(),
+
+            #[cfg(feature = "zstd_support")]
+            WriteDecoder::Zstd(mut decoder) => {
+                decoder.finish()?;
+                Ok(decoder.into_inner().0)
+            }
+
+            #[cfg(feature = "brotli_support")]
+            WriteDecoder::Brotli(mut decoder) => {
+                decoder.close()?;
+                decoder
+                    .into_inner()
+                    .map_err(|_| io::ErrorKind::UnexpectedEof.into())
+            }
+
+            #[cfg(feature = "bzip2_support")]
+            WriteDecoder::Bzip2(decoder) => decoder.finish(),
+
+            #[cfg(feature = "snappy_support")]
+            WriteDecoder::Snappy(decoder) => decoder.finish(),
+
+            #[cfg(feature = "xz_support")]
+            WriteDecoder::Xz(mut decoder) => decoder.finish(),
+
+            WriteDecoder::Buffered {
+                codec,
+                buffer,
+                mut writer,
+            } => {
+                writer.write_all(&crate::decode(&buffer, codec)?)?;
+                Ok(writer)
+            }
+        }
+    }
+}
+
+impl<W: Write> Write for WriteDecoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            WriteDecoder::Identity(writer) => writer.write(buf),
+
+            #[cfg(feature = "gzip_support")]
+            WriteDecoder::Gzip(decoder) => decoder.write(buf),
+
+            #[cfg(feature = "deflate_support")]
+            WriteDecoder::Deflate(decoder) => decoder.write(buf),
+
+            #[cfg(feature = "zlib_support")]
+            WriteDecoder::Zlib(decoder) => decoder.write(buf),
+
+            #[cfg(feature = "zstd_support")]
+            WriteDecoder::Zstd(decoder) => decoder.write(buf),
+
+            #[cfg(feature = "brotli_support")]
+            WriteDecoder::Brotli(decoder) => decoder.write(buf),
+
+            #[cfg(feature = "bzip2_support")]
+            WriteDecoder::Bzip2(decoder) => decoder.write(buf),
+
+            #[cfg(feature = "snappy_support")]
+            WriteDecoder::Snappy(decoder) => decoder.write(buf),
+
+            #[cfg(feature = "xz_support")]
+            WriteDecoder::Xz(decoder) => decoder.write(buf),
+
+            WriteDecoder::Buffered { buffer, .. } => {
+                buffer.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            WriteDecoder::Identity(writer) => writer.flush(),
+
+            #[cfg(feature = "gzip_support")]
+            WriteDecoder::Gzip(decoder) => decoder.flush(),
+
+            #[cfg(feature = "deflate_support")]
+            WriteDecoder::Deflate(decoder) => decoder.flush(),
+
+            #[cfg(feature = "zlib_support")]
+            WriteDecoder::Zlib(decoder) => decoder.flush(),
+
+            #[cfg(feature = "zstd_support")]
+            WriteDecoder::Zstd(decoder) => decoder.flush(),
+
+            #[cfg(feature = "brotli_support")]
+            WriteDecoder::Brotli(decoder) => decoder.flush(),
+
+            #[cfg(feature = "bzip2_support")]
+            WriteDecoder::Bzip2(decoder) => decoder.flush(),
+
+            #[cfg(feature = "snappy_support")]
+            WriteDecoder::Snappy(decoder) => decoder.flush(),
+
+            #[cfg(feature = "xz_support")]
+            WriteDecoder::Xz(decoder) => decoder.flush(),
+
+            WriteDecoder::Buffered { .. } => Ok(()),
+        }
+    }
+}
+
+/// Encodes `data` on tokio's blocking thread pool, keeping CPU-heavy work
+/// off the async executor.
+pub async fn encode_async(data: Vec<u8>, codec: Codec, quality: Quality) -> crate::Result<Vec<u8>> {
+    tokio::task::spawn_blocking(move || crate::encode(&data, codec, quality))
+        .await
+        .map_err(|err| Error::Io(io::Error::other(err)))?
+}
+
+/// Decodes `data` on tokio's blocking thread pool, keeping CPU-heavy work
+/// off the async executor.
+pub async fn decode_async(data: Vec<u8>, codec: Codec) -> crate::Result<Vec<u8>> {
+    tokio::task::spawn_blocking(move || crate::decode(&data, codec))
+        .await
+        .map_err(|err| Error::Io(io::Error::other(err)))?
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const TEST_DATA: &[u8] = include_bytes!("ipsum.txt");
+
+    #[tokio::test]
+    async fn async_encode_matches_decode() {
+        for &codec in ALL_CODECS.iter().filter(|&&codec| is_codec_enabled(codec)) {
+            let mut encoder = AsyncEncoder::new(Vec::new(), codec, Quality::Default).unwrap();
+            for chunk in TEST_DATA.chunks(100) {
+                encoder.write_all(chunk).await.unwrap();
+            }
+            encoder.shutdown().await.unwrap();
+            let encoded = encoder.into_inner();
+            assert_eq!(decode(&encoded, codec).unwrap(), TEST_DATA, "{}", codec);
+        }
+    }
+
+    #[tokio::test]
+    async fn async_decode_matches_encode() {
+        for &codec in ALL_CODECS.iter().filter(|&&codec| is_codec_enabled(codec)) {
+            let encoded = encode(TEST_DATA, codec, Quality::Default).unwrap();
+            let mut decoder = AsyncDecoder::new(&encoded[..], codec).unwrap();
+            let mut decoded = Vec::new();
+            decoder.read_to_end(&mut decoded).await.unwrap();
+            assert_eq!(decoded, TEST_DATA, "{}", codec);
+        }
+    }
+
+    #[test]
+    fn async_write_decoder_small_writes() {
+        use std::io::Write;
+        // Large enough to span several snappy chunks and fill the bzip2
+        // output buffer.
+        let large = TEST_DATA.repeat(20);
+        for &codec in ALL_CODECS.iter().filter(|&&codec| is_codec_enabled(codec)) {
+            let data = match codec {
+                Codec::Bzip2 | Codec::Snappy => &large[..],
+                _ => TEST_DATA,
+            };
+            let encoded = encode(data, codec, Quality::Default).unwrap();
+            let mut decoder = super::WriteDecoder::new(Vec::new(), codec).unwrap();
+            for chunk in encoded.chunks(7) {
+                decoder.write_all(chunk).unwrap();
+            }
+            assert_eq!(decoder.finish().unwrap(), data, "{}", codec);
+        }
+    }
+
+    #[tokio::test]
+    async fn async_decode_truncated() {
+        let codecs = [
+            Codec::Gzip,
+            Codec::Zstd,
+            Codec::Bzip2,
+            Codec::Snappy,
+            Codec::Xz,
+            Codec::Lzma,
+        ];
+        for &codec in codecs.iter().filter(|&&codec| is_codec_enabled(codec)) {
+            let encoded = encode(TEST_DATA, codec, Quality::Default).unwrap();
+            let mut decoder = AsyncDecoder::new(&encoded[..encoded.len() - 1], codec).unwrap();
+            let mut decoded = Vec::new();
+            assert!(
+                decoder.read_to_end(&mut decoded).await.is_err(),
+                "{}",
+                codec
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn async_decode_concatenated() {
+        let codecs = [
+            Codec::Gzip,
+            Codec::Zstd,
+            Codec::Bzip2,
+            Codec::Snappy,
+            Codec::Xz,
+        ];
+        for &codec in codecs.iter().filter(|&&codec| is_codec_enabled(codec)) {
+            let encoded = encode_parallel(TEST_DATA, codec, Quality::Default, 1000, 4).unwrap();
+            let mut decoder = AsyncDecoder::new(&encoded[..], codec).unwrap();
+            let mut decoded = Vec::new();
+            decoder.read_to_end(&mut decoded).await.unwrap();
+            assert_eq!(decoded, TEST_DATA, "{}", codec);
+        }
+    }
+
+    #[tokio::test]
+    async fn async_blocking_helpers() {
+        for &codec in ALL_CODECS.iter().filter(|&&codec| is_codec_enabled(codec)) {
+            let encoded = encode_async(TEST_DATA.to_vec(), codec, Quality::Default)
+                .await
+                .unwrap();
+            assert_eq!(
+                decode_async(encoded, codec).await.unwrap(),
+                TEST_DATA,
+                "{}",
+                codec
+            );
+        }
+    }
+}
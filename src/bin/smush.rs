@@ -0,0 +1,243 @@
+use smush::{
+    decode, decode_auto, detect_candidates, encode, registry, Codec, Error, Quality, Result,
+};
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+use std::{env, fs, process};
+
+const USAGE: &str = "\
+usage: smush <command> [options] [input]
+
+commands:
+    compress      encode the input with a codec
+    decompress    decode the input, detecting the codec if none is given
+    detect        print the codecs the input may have been encoded with
+    bench         encode and decode the input with every enabled codec
+    list          print the enabled codecs
+
+options:
+    -c, --codec <codec>        codec to use, e.g. zstd
+    -q, --quality <quality>    quality to encode with, e.g. level9
+    -o, --output <file>        write to a file instead of stdout
+    -h, --help                 print this message
+
+The input is read from stdin when no file is given, or when it is `-`.";
+
+struct Args {
+    command: String,
+    codec: Option<Codec>,
+    quality: Quality,
+    input: Option<String>,
+    output: Option<String>,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = Args {
+        command: String::new(),
+        codec: None,
+        quality: Quality::Default,
+        input: None,
+        output: None,
+    };
+    let mut iter = env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next().ok_or_else(|| {
+                Error::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("missing value for {}", arg),
+                ))
+            })
+        };
+        match arg.as_str() {
+            "-c" | "--codec" => args.codec = Some(value()?.parse()?),
+            "-q" | "--quality" => args.quality = value()?.parse()?,
+            "-o" | "--output" => args.output = Some(value()?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ if args.command.is_empty() => args.command = arg,
+            _ if args.input.is_none() => args.input = Some(arg),
+            _ => {
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unexpected argument: {}", arg),
+                )))
+            }
+        }
+    }
+    Ok(args)
+}
+
+fn read_input(args: &Args) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    match args.input.as_deref() {
+        None | Some("-") => {
+            io::stdin().read_to_end(&mut buf)?;
+        }
+        Some(path) => buf = fs::read(path)?,
+    }
+    Ok(buf)
+}
+
+fn write_output(args: &Args, data: &[u8]) -> Result<()> {
+    match args.output.as_deref() {
+        None | Some("-") => io::stdout().write_all(data)?,
+        Some(path) => fs::write(path, data)?,
+    }
+    Ok(())
+}
+
+fn require_codec(args: &Args) -> Result<Codec> {
+    args.codec.ok_or_else(|| {
+        Error::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} requires --codec", args.command),
+        ))
+    })
+}
+
+fn print_delta(identity: f32, encoded: f32, codec: Codec, quality: Quality, timings: &str) {
+    let delta = (identity - encoded) / identity * 100f32;
+    if delta > 0f32 {
+        eprintln!(
+            "[{}] - {} is {:.2}% smaller than identity - {}",
+            quality, codec, delta, timings
+        );
+    } else {
+        eprintln!(
+            "[{}] - {} is {:.2}% larger than identity - {}",
+            quality,
+            codec,
+            delta.abs(),
+            timings
+        );
+    }
+}
+
+fn timed<T>(f: impl FnOnce() -> Result<T>) -> Result<(Duration, T)> {
+    let start_time = Instant::now();
+    let result = f()?;
+    Ok((start_time.elapsed(), result))
+}
+
+fn compress(args: &Args) -> Result<()> {
+    let codec = require_codec(args)?;
+    let data = read_input(args)?;
+    let (elapsed, encoded) = timed(|| encode(&data, codec, args.quality))?;
+    write_output(args, &encoded)?;
+    print_delta(
+        data.len() as f32,
+        encoded.len() as f32,
+        codec,
+        args.quality,
+        &format!("encode: {}", elapsed.as_secs_f32()),
+    );
+    Ok(())
+}
+
+fn decompress(args: &Args) -> Result<()> {
+    let data = read_input(args)?;
+    let (elapsed, decoded) = match args.codec {
+        Some(codec) => timed(|| decode(&data, codec))?,
+        None => timed(|| decode_auto(&data))?,
+    };
+    write_output(args, &decoded)?;
+    eprintln!(
+        "decoded {} bytes to {} bytes - decode: {}",
+        data.len(),
+        decoded.len(),
+        elapsed.as_secs_f32()
+    );
+    Ok(())
+}
+
+fn detect(args: &Args) -> Result<()> {
+    let data = read_input(args)?;
+    let candidates = detect_candidates(&data);
+    if candidates.is_empty() {
+        return Err(Error::Undetected);
+    }
+    for candidate in candidates {
+        println!("{} ({:.2})", candidate.codec, candidate.confidence);
+    }
+    Ok(())
+}
+
+fn bench(args: &Args) -> Result<()> {
+    let data = read_input(args)?;
+    let codecs: Vec<Codec> = match args.codec {
+        Some(codec) => vec![codec],
+        None => registry()
+            .names()
+            .into_iter()
+            .map(str::parse)
+            .collect::<Result<_>>()?,
+    };
+    for codec in codecs {
+        let (encode_elapsed, encoded) = timed(|| encode(&data, codec, args.quality))?;
+        let (decode_elapsed, decoded) = timed(|| decode(&encoded, codec))?;
+        if decoded != data {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} did not round trip", codec),
+            )));
+        }
+        print_delta(
+            data.len() as f32,
+            encoded.len() as f32,
+            codec,
+            args.quality,
+            &format!(
+                "encode: {}, decode: {}",
+                encode_elapsed.as_secs_f32(),
+                decode_elapsed.as_secs_f32()
+            ),
+        );
+    }
+    Ok(())
+}
+
+fn list() -> Result<()> {
+    let registry = registry();
+    let names = registry.names();
+    let width = names.iter().map(|name| name.len()).max().unwrap_or(0);
+    for name in names {
+        let capabilities = registry.get(name).unwrap().capabilities();
+        let mut tags = Vec::new();
+        if capabilities.compression {
+            tags.push("compression");
+        }
+        if capabilities.text {
+            tags.push("text");
+        }
+        if capabilities.quality {
+            tags.push("quality");
+        }
+        println!("{:<width$} {}", name, tags.join(", "), width = width);
+    }
+    Ok(())
+}
+
+fn run() -> Result<()> {
+    let args = parse_args()?;
+    match args.command.as_str() {
+        "compress" => compress(&args),
+        "decompress" => decompress(&args),
+        "detect" => detect(&args),
+        "bench" => bench(&args),
+        "list" => list(),
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,38 @@
+// https://tools.ietf.org/html/rfc4648#section-6
+// https://www.crockford.com/base32.html
+
+use std::sync::OnceLock;
+
+fn crockford() -> &'static data_encoding::Encoding {
+    static CROCKFORD: OnceLock<data_encoding::Encoding> = OnceLock::new();
+    CROCKFORD.get_or_init(|| {
+        let mut spec = data_encoding::Specification::new();
+        spec.symbols.push_str("0123456789ABCDEFGHJKMNPQRSTVWXYZ");
+        // Decoding is case insensitive and maps the easily confused letters
+        // to the digits they resemble.
+        spec.translate.from.push_str("abcdefghjkmnpqrstvwxyzIiLlOo");
+        spec.translate.to.push_str("ABCDEFGHJKMNPQRSTVWXYZ111100");
+        spec.ignore.push('-');
+        spec.encoding().unwrap()
+    })
+}
+
+static BASE32: data_encoding::Encoding = data_encoding::BASE32;
+
+fn encoding(codec: crate::Codec) -> &'static data_encoding::Encoding {
+    match codec {
+        crate::Codec::Base32Crockford => crockford(),
+        _ => &BASE32,
+    }
+}
+
+pub fn encode(data: &[u8], codec: crate::Codec) -> crate::Result<Vec<u8>> {
+    Ok(encoding(codec).encode(data).into_bytes())
+}
+
+pub fn decode(data: &[u8], codec: crate::Codec) -> crate::Result<Vec<u8>> {
+    let data = super::strip_whitespace(data);
+    encoding(codec)
+        .decode(&data)
+        .map_err(|err| crate::Error::corrupt(codec, err))
+}
@@ -1,20 +1,48 @@
-pub fn encode(data: &[u8], _: crate::Quality) -> std::io::Result<Vec<u8>> {
-    let base58_str = bs58::encode(data).into_string();
+// https://en.bitcoin.it/wiki/Base58Check_encoding
+
+fn alphabet(codec: crate::Codec) -> &'static bs58::Alphabet {
+    match codec {
+        crate::Codec::Base58Ripple | crate::Codec::Base58CheckRipple => bs58::Alphabet::RIPPLE,
+        crate::Codec::Base58Flickr | crate::Codec::Base58CheckFlickr => bs58::Alphabet::FLICKR,
+        _ => bs58::Alphabet::BITCOIN,
+    }
+}
+
+fn is_check(codec: crate::Codec) -> bool {
+    matches!(
+        codec,
+        crate::Codec::Base58Check
+            | crate::Codec::Base58CheckRipple
+            | crate::Codec::Base58CheckFlickr
+    )
+}
+
+pub fn encode(data: &[u8], codec: crate::Codec) -> crate::Result<Vec<u8>> {
+    let encoder = bs58::encode(data).with_prepared_alphabet(alphabet(codec));
+    let base58_str = if is_check(codec) {
+        encoder.with_check().into_string()
+    } else {
+        encoder.into_string()
+    };
     Ok(Vec::from(base58_str))
 }
 
-pub fn decode(data: &[u8]) -> std::io::Result<Vec<u8>> {
-    match std::str::from_utf8(data) {
-        Err(err) => Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("failed to decode with base58 - details: {:?}", err),
-        )),
-        Ok(ref base58_str) => match bs58::decode(base58_str.trim()).into_vec() {
-            Err(err) => Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("failed to decode with base58 - details: {:?}", err),
-            )),
-            Ok(buf) => Ok(buf),
+pub fn decode(data: &[u8], codec: crate::Codec) -> crate::Result<Vec<u8>> {
+    let base58_str = std::str::from_utf8(data).map_err(|err| crate::Error::corrupt(codec, err))?;
+    let decoder = bs58::decode(base58_str.trim()).with_prepared_alphabet(alphabet(codec));
+    let decoded = if is_check(codec) {
+        decoder.with_check(None).into_vec()
+    } else {
+        decoder.into_vec()
+    };
+    decoded.map_err(|err| match err {
+        bs58::decode::Error::InvalidChecksum {
+            checksum,
+            expected_checksum,
+        } => crate::Error::ChecksumMismatch {
+            expected: u32::from_be_bytes(expected_checksum),
+            found: u32::from_be_bytes(checksum),
         },
-    }
+        err => crate::Error::corrupt(codec, err),
+    })
 }
@@ -0,0 +1,27 @@
+// https://tools.ietf.org/html/rfc4648#section-4
+// https://tools.ietf.org/html/rfc4648#section-5
+
+static BASE64: data_encoding::Encoding = data_encoding::BASE64;
+static BASE64URL: data_encoding::Encoding = data_encoding::BASE64URL;
+static BASE64_NOPAD: data_encoding::Encoding = data_encoding::BASE64_NOPAD;
+static BASE64URL_NOPAD: data_encoding::Encoding = data_encoding::BASE64URL_NOPAD;
+
+fn encoding(codec: crate::Codec) -> &'static data_encoding::Encoding {
+    match codec {
+        crate::Codec::Base64Url => &BASE64URL,
+        crate::Codec::Base64NoPad => &BASE64_NOPAD,
+        crate::Codec::Base64UrlNoPad => &BASE64URL_NOPAD,
+        _ => &BASE64,
+    }
+}
+
+pub fn encode(data: &[u8], codec: crate::Codec) -> crate::Result<Vec<u8>> {
+    Ok(encoding(codec).encode(data).into_bytes())
+}
+
+pub fn decode(data: &[u8], codec: crate::Codec) -> crate::Result<Vec<u8>> {
+    let data = super::strip_whitespace(data);
+    encoding(codec)
+        .decode(&data)
+        .map_err(|err| crate::Error::corrupt(codec, err))
+}
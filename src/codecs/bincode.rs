@@ -1,19 +1,67 @@
-pub fn encode(data: &[u8], _: crate::Quality) -> std::io::Result<Vec<u8>> {
-    match bincode::serialize(&data[..]) {
-        Err(err) => Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("failed to encode with bincode - details: {:?}", err),
-        )),
-        Ok(buf) => Ok(buf),
-    }
+use bincode::Options;
+
+/// Applies the integer encoding and endianness of `BinCodeOptions` to the
+/// default bincode configuration, binding the result to `$config`. Each
+/// combination is a different type, so this expands `$body` once per arm.
+macro_rules! with_config {
+    ($options:expr, |$config:ident| $body:expr) => {{
+        let config = bincode::DefaultOptions::new().allow_trailing_bytes();
+        match ($options.int_encoding, $options.endian) {
+            (crate::BinCodeIntEncoding::Fixed, crate::BinCodeEndian::Little) => {
+                let $config = config.with_fixint_encoding().with_little_endian();
+                $body
+            }
+            (crate::BinCodeIntEncoding::Fixed, crate::BinCodeEndian::Big) => {
+                let $config = config.with_fixint_encoding().with_big_endian();
+                $body
+            }
+            (crate::BinCodeIntEncoding::Varint, crate::BinCodeEndian::Little) => {
+                let $config = config.with_varint_encoding().with_little_endian();
+                $body
+            }
+            (crate::BinCodeIntEncoding::Varint, crate::BinCodeEndian::Big) => {
+                let $config = config.with_varint_encoding().with_big_endian();
+                $body
+            }
+        }
+    }};
+}
+
+pub fn encode(data: &[u8], quality: crate::Quality) -> crate::Result<Vec<u8>> {
+    encode_with_options(data, &crate::BinCodeOptions::new(quality))
+}
+
+pub fn encode_with_options(data: &[u8], options: &crate::BinCodeOptions) -> crate::Result<Vec<u8>> {
+    with_config!(options, |config| config.serialize(data))
+        .map_err(|err| crate::Error::Io(std::io::Error::other(err)))
+}
+
+pub fn decode(data: &[u8]) -> crate::Result<Vec<u8>> {
+    decode_with_options(data, &crate::BinCodeOptions::default())
+}
+
+pub fn decode_with_options(data: &[u8], options: &crate::BinCodeOptions) -> crate::Result<Vec<u8>> {
+    // bincode does not count single bytes against its own limit, so check the
+    // size implied by the length prefix before reading the payload.
+    with_config!(options, |config| {
+        if let Some(limit) = options.limit {
+            let len: u64 = config.deserialize(data).map_err(corrupt)?;
+            let size = config.serialized_size(&len).map_err(corrupt)?;
+            if size.saturating_add(len) > limit {
+                return Err(crate::Error::LimitExceeded { limit });
+            }
+        }
+        config.deserialize(data).map_err(corrupt)
+    })
+}
+
+fn corrupt(err: bincode::Error) -> crate::Error {
+    crate::Error::corrupt(crate::Codec::BinCode, err)
 }
 
-pub fn decode(data: &[u8]) -> std::io::Result<Vec<u8>> {
-    match bincode::deserialize(&data[..]) {
-        Err(err) => Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("failed to decode with bincode - details: {:?}", err),
-        )),
-        Ok(buf) => Ok(buf),
-    }
+/// Reads the length prefix bincode writes before the serialized bytes.
+pub fn decoded_len(data: &[u8]) -> Option<u64> {
+    let mut len = [0u8; 8];
+    len.copy_from_slice(data.get(..8)?);
+    Some(u64::from_le_bytes(len))
 }
@@ -3,6 +3,10 @@
 // https://blogs.dropbox.com/tech/2016/06/lossless-compression-with-brotli/
 // https://hacks.mozilla.org/2015/11/better-than-gzip-compression-with-brotli/
 
+pub fn level_range() -> std::ops::RangeInclusive<i32> {
+    0..=11
+}
+
 fn quality_to_codec(quality: crate::Quality) -> i32 {
     match quality {
         crate::Quality::Default => 6,
@@ -16,18 +20,37 @@ fn quality_to_codec(quality: crate::Quality) -> i32 {
         crate::Quality::Level8 => 8,
         crate::Quality::Level9 => 9,
         crate::Quality::Maximum => 11,
+        crate::Quality::Exact(level) => {
+            let range = level_range();
+            level.clamp(*range.start(), *range.end())
+        }
+    }
+}
+
+fn encoder_params(options: &crate::BrotliOptions) -> brotli::enc::BrotliEncoderParams {
+    let mut params = brotli::enc::BrotliEncoderParams {
+        quality: quality_to_codec(options.quality),
+        lgwin: options.window as i32,
+        ..Default::default()
+    };
+    if let Some(block) = options.block {
+        params.lgblock = block as i32;
+    }
+    if options.text {
+        params.mode = brotli::enc::backward_references::BrotliEncoderMode::BROTLI_MODE_TEXT;
     }
+    params
+}
+
+pub fn encode(data: &[u8], quality: crate::Quality) -> crate::Result<Vec<u8>> {
+    encode_with_options(data, &crate::BrotliOptions::new(quality))
 }
 
-pub fn encode(data: &[u8], quality: crate::Quality) -> std::io::Result<Vec<u8>> {
+pub fn encode_with_options(data: &[u8], options: &crate::BrotliOptions) -> crate::Result<Vec<u8>> {
     use std::io::Read;
     let mut buf = Vec::new();
     let buffer_size = 4096;
-    let params = brotli::enc::BrotliEncoderParams {
-        quality: quality_to_codec(quality),
-        lgwin: 20,
-        ..Default::default()
-    };
+    let params = encoder_params(options);
     std::io::BufReader::new(brotli::CompressorReader::with_params(
         data,
         buffer_size,
@@ -37,13 +60,39 @@ pub fn encode(data: &[u8], quality: crate::Quality) -> std::io::Result<Vec<u8>>
     Ok(buf)
 }
 
-pub fn decode(data: &[u8]) -> std::io::Result<Vec<u8>> {
+pub fn decode(data: &[u8]) -> crate::Result<Vec<u8>> {
     use std::io::Write;
-    let mut buf = Vec::new();
-    let mut writer = std::io::Cursor::new(&mut buf);
-    let mut decoder = brotli::DecompressorWriter::new(&mut writer, 4096);
-    decoder.write_all(&data[..])?;
-    drop(decoder);
-    drop(writer);
-    Ok(buf)
+    let mut decoder = brotli::DecompressorWriter::new(Vec::new(), 4096);
+    decoder.write_all(data).map_err(corrupt)?;
+    decoder.close().map_err(corrupt)?;
+    match decoder.into_inner() {
+        Ok(buf) => Ok(buf),
+        Err(_) => Err(corrupt(std::io::ErrorKind::UnexpectedEof.into())),
+    }
+}
+
+fn corrupt(err: std::io::Error) -> crate::Error {
+    crate::Error::corrupt(crate::Codec::Brotli, err)
+}
+
+pub fn encoder<W: std::io::Write>(
+    writer: W,
+    quality: crate::Quality,
+) -> std::io::Result<brotli::CompressorWriter<W>> {
+    Ok(brotli::CompressorWriter::with_params(
+        writer,
+        4096,
+        &encoder_params(&crate::BrotliOptions::new(quality)),
+    ))
+}
+
+pub fn decoder<R: std::io::Read>(reader: R) -> std::io::Result<brotli::Decompressor<R>> {
+    Ok(brotli::Decompressor::new(reader, 4096))
+}
+
+#[cfg(feature = "async")]
+pub fn write_decoder<W: std::io::Write>(
+    writer: W,
+) -> std::io::Result<brotli::DecompressorWriter<W>> {
+    Ok(brotli::DecompressorWriter::new(writer, 4096))
 }
@@ -0,0 +1,132 @@
+pub fn level_range() -> std::ops::RangeInclusive<i32> {
+    1..=9
+}
+
+/// Maps the quality to a block size, in units of 100k.
+fn quality_to_codec(quality: crate::Quality) -> u32 {
+    match quality {
+        crate::Quality::Default => 6,
+        crate::Quality::Level1 => 1,
+        crate::Quality::Level2 => 2,
+        crate::Quality::Level3 => 3,
+        crate::Quality::Level4 => 4,
+        crate::Quality::Level5 => 5,
+        crate::Quality::Level6 => 6,
+        crate::Quality::Level7 => 7,
+        crate::Quality::Level8 => 8,
+        crate::Quality::Level9 => 9,
+        crate::Quality::Maximum => 9,
+        crate::Quality::Exact(level) => {
+            let range = level_range();
+            level.clamp(*range.start(), *range.end()) as u32
+        }
+    }
+}
+
+pub fn encode(data: &[u8], quality: crate::Quality) -> crate::Result<Vec<u8>> {
+    use std::io::Write;
+    let mut encoder = bzip2::write::BzEncoder::new(
+        Vec::new(),
+        bzip2::Compression::new(quality_to_codec(quality)),
+    );
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+pub fn decode(data: &[u8]) -> crate::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut buf = Vec::new();
+    // Concatenated streams (e.g. from pbzip2 or `cat a.bz2 b.bz2`) are valid
+    // bzip2, so decode every stream rather than stopping after the first.
+    bzip2::read::MultiBzDecoder::new(data)
+        .read_to_end(&mut buf)
+        .map_err(corrupt)?;
+    Ok(buf)
+}
+
+fn corrupt(err: std::io::Error) -> crate::Error {
+    crate::Error::corrupt(crate::Codec::Bzip2, err)
+}
+
+pub fn encoder<W: std::io::Write>(
+    writer: W,
+    quality: crate::Quality,
+) -> std::io::Result<bzip2::write::BzEncoder<W>> {
+    Ok(bzip2::write::BzEncoder::new(
+        writer,
+        bzip2::Compression::new(quality_to_codec(quality)),
+    ))
+}
+
+pub fn decoder<R: std::io::Read>(reader: R) -> std::io::Result<bzip2::read::MultiBzDecoder<R>> {
+    Ok(bzip2::read::MultiBzDecoder::new(reader))
+}
+
+#[cfg(feature = "async")]
+pub fn write_decoder<W: std::io::Write>(writer: W) -> std::io::Result<WriteDecoder<W>> {
+    Ok(WriteDecoder {
+        decompress: None,
+        buf: Vec::with_capacity(32 * 1024),
+        writer,
+    })
+}
+
+/// Decodes bzip2 data as it is written, continuing with the next stream when
+/// one ends. `bzip2::write::BzDecoder` stops after the first stream.
+#[cfg(feature = "async")]
+pub struct WriteDecoder<W> {
+    /// The decompressor for the current stream, or `None` between streams.
+    decompress: Option<bzip2::Decompress>,
+    buf: Vec<u8>,
+    writer: W,
+}
+
+#[cfg(feature = "async")]
+impl<W: std::io::Write> WriteDecoder<W> {
+    /// Decodes `data`, writing out everything the decompressor produces.
+    fn run(&mut self, mut data: &[u8]) -> std::io::Result<()> {
+        loop {
+            let decompress = match self.decompress.as_mut() {
+                Some(decompress) => decompress,
+                None if data.is_empty() => return Ok(()),
+                None => self.decompress.insert(bzip2::Decompress::new(false)),
+            };
+            let before = decompress.total_in();
+            self.buf.clear();
+            let status = decompress
+                .decompress_vec(data, &mut self.buf)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+            data = &data[(decompress.total_in() - before) as usize..];
+            self.writer.write_all(&self.buf)?;
+            if status == bzip2::Status::StreamEnd {
+                self.decompress = None;
+            } else if data.is_empty() && self.buf.len() < self.buf.capacity() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Writes out any remaining data, returning the inner writer.
+    pub fn finish(mut self) -> std::io::Result<W> {
+        self.run(&[])?;
+        match self.decompress {
+            Some(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "truncated bzip2 stream",
+            )),
+            None => Ok(self.writer),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<W: std::io::Write> std::io::Write for WriteDecoder<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.run(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
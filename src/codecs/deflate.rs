@@ -1,3 +1,7 @@
+pub fn level_range() -> std::ops::RangeInclusive<i32> {
+    0..=9
+}
+
 fn quality_to_codec(quality: crate::Quality) -> u32 {
     match quality {
         crate::Quality::Default => 6,
@@ -11,10 +15,14 @@ fn quality_to_codec(quality: crate::Quality) -> u32 {
         crate::Quality::Level8 => 8,
         crate::Quality::Level9 => 9,
         crate::Quality::Maximum => 9,
+        crate::Quality::Exact(level) => {
+            let range = level_range();
+            level.clamp(*range.start(), *range.end()) as u32
+        }
     }
 }
 
-pub fn encode(data: &[u8], quality: crate::Quality) -> std::io::Result<Vec<u8>> {
+pub fn encode(data: &[u8], quality: crate::Quality) -> crate::Result<Vec<u8>> {
     use flate2::{read::DeflateEncoder, Compression};
     use std::io::Read;
     let mut buf = Vec::new();
@@ -26,12 +34,38 @@ pub fn encode(data: &[u8], quality: crate::Quality) -> std::io::Result<Vec<u8>>
     Ok(buf)
 }
 
-pub fn decode(data: &[u8]) -> std::io::Result<Vec<u8>> {
+pub fn decode(data: &[u8]) -> crate::Result<Vec<u8>> {
     use flate2::write::DeflateDecoder;
     use std::io::Write;
     let mut buf = Vec::new();
     let mut decoder = DeflateDecoder::new(buf);
-    decoder.write_all(&data[..])?;
-    buf = decoder.finish()?;
+    decoder.write_all(data).map_err(corrupt)?;
+    buf = decoder.finish().map_err(corrupt)?;
     Ok(buf)
 }
+
+fn corrupt(err: std::io::Error) -> crate::Error {
+    crate::Error::corrupt(crate::Codec::Deflate, err)
+}
+
+pub fn encoder<W: std::io::Write>(
+    writer: W,
+    quality: crate::Quality,
+) -> std::io::Result<flate2::write::DeflateEncoder<W>> {
+    use flate2::{write::DeflateEncoder, Compression};
+    Ok(DeflateEncoder::new(
+        writer,
+        Compression::new(quality_to_codec(quality)),
+    ))
+}
+
+pub fn decoder<R: std::io::Read>(reader: R) -> std::io::Result<flate2::read::DeflateDecoder<R>> {
+    Ok(flate2::read::DeflateDecoder::new(reader))
+}
+
+#[cfg(feature = "async")]
+pub fn write_decoder<W: std::io::Write>(
+    writer: W,
+) -> std::io::Result<flate2::write::DeflateDecoder<W>> {
+    Ok(flate2::write::DeflateDecoder::new(writer))
+}
@@ -1,3 +1,7 @@
+pub fn level_range() -> std::ops::RangeInclusive<i32> {
+    0..=9
+}
+
 fn quality_to_codec(quality: crate::Quality) -> u32 {
     match quality {
         crate::Quality::Default => 6,
@@ -11,10 +15,14 @@ fn quality_to_codec(quality: crate::Quality) -> u32 {
         crate::Quality::Level8 => 8,
         crate::Quality::Level9 => 9,
         crate::Quality::Maximum => 9,
+        crate::Quality::Exact(level) => {
+            let range = level_range();
+            level.clamp(*range.start(), *range.end()) as u32
+        }
     }
 }
 
-pub fn encode(data: &[u8], quality: crate::Quality) -> std::io::Result<Vec<u8>> {
+pub fn encode(data: &[u8], quality: crate::Quality) -> crate::Result<Vec<u8>> {
     use flate2::{read::GzEncoder, Compression};
     use std::io::Read;
     let mut buf = Vec::new();
@@ -26,12 +34,119 @@ pub fn encode(data: &[u8], quality: crate::Quality) -> std::io::Result<Vec<u8>>
     Ok(buf)
 }
 
-pub fn decode(data: &[u8]) -> std::io::Result<Vec<u8>> {
-    use flate2::write::GzDecoder;
+pub fn encode_with_header(
+    data: &[u8],
+    quality: crate::Quality,
+    header: &crate::GzipHeader,
+) -> crate::Result<Vec<u8>> {
+    use std::io::Write;
+    let invalid = |details| std::io::Error::new(std::io::ErrorKind::InvalidInput, details);
+    let mut builder = flate2::GzBuilder::new()
+        .mtime(header.mtime)
+        .operating_system(header.operating_system);
+    if let Some(filename) = &header.filename {
+        if filename.contains(&0) {
+            return Err(invalid("gzip filename contains a zero byte").into());
+        }
+        builder = builder.filename(filename.clone());
+    }
+    if let Some(comment) = &header.comment {
+        if comment.contains(&0) {
+            return Err(invalid("gzip comment contains a zero byte").into());
+        }
+        builder = builder.comment(comment.clone());
+    }
+    if let Some(extra) = &header.extra {
+        if extra.len() > u16::MAX as usize {
+            return Err(invalid("gzip extra field is longer than 65535 bytes").into());
+        }
+        builder = builder.extra(extra.clone());
+    }
+
+    let mut encoder = builder.write(
+        Vec::new(),
+        flate2::Compression::new(quality_to_codec(quality)),
+    );
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+pub fn decode(data: &[u8]) -> crate::Result<Vec<u8>> {
+    use flate2::write::MultiGzDecoder;
     use std::io::Write;
     let mut buf = Vec::new();
-    let mut decoder = GzDecoder::new(buf);
-    decoder.write_all(&data[..])?;
-    buf = decoder.finish()?;
+    let mut decoder = MultiGzDecoder::new(buf);
+    decoder.write_all(data).map_err(corrupt)?;
+    buf = decoder.finish().map_err(corrupt)?;
     Ok(buf)
 }
+
+pub fn decode_with_header(data: &[u8]) -> crate::Result<(Vec<u8>, crate::GzipHeader)> {
+    let (buf, members) = decode_members(data)?;
+    match members.into_iter().next() {
+        Some(member) => Ok((buf, member.header)),
+        None => Err(corrupt(std::io::ErrorKind::UnexpectedEof.into())),
+    }
+}
+
+pub fn decode_members(data: &[u8]) -> crate::Result<(Vec<u8>, Vec<crate::GzipMember>)> {
+    use std::io::Read;
+    let mut buf = Vec::new();
+    let mut members = Vec::new();
+    let mut rest = data;
+    // The buffered decoder consumes exactly one member from the slice, so the
+    // remainder starts at the next member.
+    while !rest.is_empty() {
+        let start = data.len() - rest.len();
+        let decoded_start = buf.len();
+        let mut decoder = flate2::bufread::GzDecoder::new(rest);
+        decoder.read_to_end(&mut buf).map_err(corrupt)?;
+        let header = match decoder.header() {
+            Some(header) => to_header(header),
+            None => return Err(corrupt(std::io::ErrorKind::UnexpectedEof.into())),
+        };
+        rest = decoder.into_inner();
+        members.push(crate::GzipMember {
+            header,
+            compressed: start..data.len() - rest.len(),
+            decoded: decoded_start..buf.len(),
+        });
+    }
+    Ok((buf, members))
+}
+
+fn to_header(header: &flate2::GzHeader) -> crate::GzipHeader {
+    crate::GzipHeader {
+        filename: header.filename().map(<[u8]>::to_vec),
+        comment: header.comment().map(<[u8]>::to_vec),
+        extra: header.extra().map(<[u8]>::to_vec),
+        mtime: header.mtime(),
+        operating_system: header.operating_system(),
+    }
+}
+
+fn corrupt(err: std::io::Error) -> crate::Error {
+    crate::Error::corrupt(crate::Codec::Gzip, err)
+}
+
+pub fn encoder<W: std::io::Write>(
+    writer: W,
+    quality: crate::Quality,
+) -> std::io::Result<flate2::write::GzEncoder<W>> {
+    use flate2::{write::GzEncoder, Compression};
+    Ok(GzEncoder::new(
+        writer,
+        Compression::new(quality_to_codec(quality)),
+    ))
+}
+
+pub fn decoder<R: std::io::Read>(reader: R) -> std::io::Result<flate2::read::MultiGzDecoder<R>> {
+    Ok(flate2::read::MultiGzDecoder::new(reader))
+}
+
+#[cfg(feature = "async")]
+pub fn write_decoder<W: std::io::Write>(
+    writer: W,
+) -> std::io::Result<flate2::write::MultiGzDecoder<W>> {
+    Ok(flate2::write::MultiGzDecoder::new(writer))
+}
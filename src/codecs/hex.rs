@@ -0,0 +1,10 @@
+pub fn encode(data: &[u8], _: crate::Codec) -> crate::Result<Vec<u8>> {
+    Ok(data_encoding::HEXLOWER.encode(data).into_bytes())
+}
+
+pub fn decode(data: &[u8], codec: crate::Codec) -> crate::Result<Vec<u8>> {
+    let data = super::strip_whitespace(data);
+    data_encoding::HEXLOWER_PERMISSIVE
+        .decode(&data)
+        .map_err(|err| crate::Error::corrupt(codec, err))
+}
@@ -1,3 +1,9 @@
+use std::convert::TryFrom;
+
+pub fn level_range() -> std::ops::RangeInclusive<i32> {
+    0..=12
+}
+
 fn quality_to_codec(quality: crate::Quality) -> u32 {
     match quality {
         crate::Quality::Default => 6,
@@ -11,34 +17,239 @@ fn quality_to_codec(quality: crate::Quality) -> u32 {
         crate::Quality::Level8 => 8,
         crate::Quality::Level9 => 9,
         crate::Quality::Maximum => 9,
+        crate::Quality::Exact(level) => {
+            let range = level_range();
+            level.clamp(*range.start(), *range.end()) as u32
+        }
+    }
+}
+
+/// The lowest level at which lz4 uses its high compression (HC) encoder.
+const HC_LEVEL_MIN: u32 = 3;
+
+fn options_to_level(options: &crate::Lz4Options) -> u32 {
+    let level = quality_to_codec(options.quality);
+    match options.mode {
+        crate::Lz4Mode::Auto => level,
+        crate::Lz4Mode::Fast => 0,
+        crate::Lz4Mode::HighCompression => level.max(HC_LEVEL_MIN),
     }
 }
 
-pub fn encode(data: &[u8], quality: crate::Quality) -> std::io::Result<Vec<u8>> {
+pub fn encode(data: &[u8], quality: crate::Quality) -> crate::Result<Vec<u8>> {
+    encode_with_options(data, &crate::Lz4Options::new(quality))
+}
+
+pub fn encode_with_options(data: &[u8], options: &crate::Lz4Options) -> crate::Result<Vec<u8>> {
+    use lz4::liblz4::BlockChecksum;
+    use lz4::{BlockMode, BlockSize, ContentChecksum};
+    let block_size = match options.block_size {
+        crate::Lz4BlockSize::Default => BlockSize::Default,
+        crate::Lz4BlockSize::Max64KB => BlockSize::Max64KB,
+        crate::Lz4BlockSize::Max256KB => BlockSize::Max256KB,
+        crate::Lz4BlockSize::Max1MB => BlockSize::Max1MB,
+        crate::Lz4BlockSize::Max4MB => BlockSize::Max4MB,
+    };
+    let block_mode = match options.independent_blocks {
+        true => BlockMode::Independent,
+        false => BlockMode::Linked,
+    };
+    let checksum = match options.content_checksum {
+        true => ContentChecksum::ChecksumEnabled,
+        false => ContentChecksum::NoChecksum,
+    };
+    let block_checksum = match options.block_checksum {
+        true => BlockChecksum::BlockChecksumEnabled,
+        false => BlockChecksum::NoBlockChecksum,
+    };
+
     let mut buf = Vec::new();
-    let mut writer = std::io::Cursor::new(&mut buf);
     let mut encoder = lz4::EncoderBuilder::new()
-        .level(quality_to_codec(quality))
-        .build(&mut writer)?;
+        .level(options_to_level(options))
+        .block_size(block_size)
+        .block_mode(block_mode)
+        .checksum(checksum)
+        .block_checksum(block_checksum)
+        .build(&mut buf)?;
     std::io::copy(&mut std::io::Cursor::new(data), &mut encoder)?;
     let (_, result) = encoder.finish();
-    match result {
-        Err(err) => Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("failed to encode with lz4 - details: {:?}", err),
-        )),
-        Ok(_) => {
-            drop(writer);
-            Ok(buf)
-        }
-    }
+    result?;
+    Ok(buf)
 }
 
-pub fn decode(data: &[u8]) -> std::io::Result<Vec<u8>> {
+pub fn decode(data: &[u8]) -> crate::Result<Vec<u8>> {
+    use std::io::Read;
     let mut buf = Vec::new();
-    let mut writer = std::io::Cursor::new(&mut buf);
-    let mut decoder = lz4::Decoder::new(std::io::Cursor::new(data))?;
-    std::io::copy(&mut decoder, &mut writer)?;
-    drop(writer);
+    let mut decoder = MultiDecoder::new(data).map_err(corrupt)?;
+    decoder.read_to_end(&mut buf).map_err(corrupt)?;
     Ok(buf)
 }
+
+fn corrupt(err: std::io::Error) -> crate::Error {
+    crate::Error::corrupt(crate::Codec::Lz4, err)
+}
+
+pub fn encode_block(data: &[u8], quality: crate::Quality) -> crate::Result<Vec<u8>> {
+    encode_block_with_options(data, &crate::Lz4Options::new(quality))
+}
+
+pub fn encode_block_with_options(
+    data: &[u8],
+    options: &crate::Lz4Options,
+) -> crate::Result<Vec<u8>> {
+    use lz4::block::CompressionMode;
+    let mode = match options_to_level(options) {
+        level if level < HC_LEVEL_MIN => CompressionMode::DEFAULT,
+        level => CompressionMode::HIGHCOMPRESSION(level as i32),
+    };
+    Ok(lz4::block::compress(
+        data,
+        Some(mode),
+        options.prepend_size,
+    )?)
+}
+
+pub fn decode_block(data: &[u8]) -> crate::Result<Vec<u8>> {
+    decode_block_with_options(data, &crate::Lz4Options::default())
+}
+
+pub fn decode_block_with_options(
+    data: &[u8],
+    options: &crate::Lz4Options,
+) -> crate::Result<Vec<u8>> {
+    let uncompressed_size = match (options.prepend_size, options.uncompressed_size) {
+        (true, _) => None,
+        (false, Some(size)) => match i32::try_from(size) {
+            Ok(size) => Some(size),
+            Err(_) => {
+                return Err(crate::Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "lz4 blocks cannot be larger than `i32::MAX` bytes",
+                )))
+            }
+        },
+        (false, None) => {
+            return Err(crate::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "lz4 blocks without a size prefix require `uncompressed_size`",
+            )))
+        }
+    };
+    // lz4 allocates whatever the size prefix asks for, so check it against
+    // the most the block could expand to first.
+    let prefix = match uncompressed_size {
+        None => decoded_len_block(data),
+        Some(_) => None,
+    };
+    if let Some(len) = prefix {
+        let max = (data.len() as u64 - 4) * MAX_EXPANSION;
+        if len > max || len > MAX_INPUT_SIZE {
+            return Err(crate::Error::corrupt(
+                crate::Codec::Lz4Block,
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "lz4 block size prefix is larger than the block can decode to",
+                ),
+            ));
+        }
+    }
+    lz4::block::decompress(data, uncompressed_size)
+        .map_err(|err| crate::Error::corrupt(crate::Codec::Lz4Block, err))
+}
+
+/// The most an lz4 block can expand by, since every byte of a match length
+/// encodes at most 255 bytes.
+const MAX_EXPANSION: u64 = 255;
+
+/// `LZ4_MAX_INPUT_SIZE`, the largest block lz4 can decode.
+const MAX_INPUT_SIZE: u64 = 0x7E00_0000;
+
+/// Reads the size prefix written before an lz4 block.
+pub fn decoded_len_block(data: &[u8]) -> Option<u64> {
+    let mut len = [0u8; 4];
+    len.copy_from_slice(data.get(..4)?);
+    Some(u64::from(u32::from_le_bytes(len)))
+}
+
+pub fn encoder<W: std::io::Write>(
+    writer: W,
+    quality: crate::Quality,
+) -> std::io::Result<lz4::Encoder<W>> {
+    lz4::EncoderBuilder::new()
+        .level(quality_to_codec(quality))
+        .build(writer)
+}
+
+pub fn decoder<R: std::io::Read>(reader: R) -> std::io::Result<MultiDecoder<R>> {
+    MultiDecoder::new(reader)
+}
+
+/// Decodes a series of concatenated lz4 frames, which is valid lz4 and is
+/// what `encode_parallel` writes.
+pub struct MultiDecoder<R> {
+    decoder: Option<lz4::Decoder<Lookahead<R>>>,
+}
+
+impl<R: std::io::Read> MultiDecoder<R> {
+    pub fn new(reader: R) -> std::io::Result<Self> {
+        let reader = Lookahead {
+            byte: None,
+            inner: reader,
+        };
+        Ok(MultiDecoder {
+            decoder: Some(lz4::Decoder::new(reader)?),
+        })
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for MultiDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let decoder = match self.decoder.as_mut() {
+                Some(decoder) => decoder,
+                None => return Ok(0),
+            };
+            let len = decoder.read(buf)?;
+            if len > 0 || buf.is_empty() {
+                return Ok(len);
+            }
+
+            // The frame is exhausted, so start on the next one unless the
+            // input is too.
+            let (mut reader, result) = self.decoder.take().unwrap().finish();
+            if result.is_err() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "truncated lz4 frame",
+                ));
+            }
+            let mut byte = [0u8];
+            if reader.inner.read(&mut byte)? == 0 {
+                return Ok(0);
+            }
+            reader.byte = Some(byte[0]);
+            self.decoder = Some(lz4::Decoder::new(reader)?);
+        }
+    }
+}
+
+/// A reader that replays a byte read to check for the end of the input.
+struct Lookahead<R> {
+    byte: Option<u8>,
+    inner: R,
+}
+
+impl<R: std::io::Read> std::io::Read for Lookahead<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.byte.take() {
+            Some(byte) if !buf.is_empty() => {
+                buf[0] = byte;
+                Ok(1)
+            }
+            byte => {
+                self.byte = byte;
+                self.inner.read(buf)
+            }
+        }
+    }
+}
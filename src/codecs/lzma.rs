@@ -0,0 +1,462 @@
+// https://github.com/tukaani-project/xz/blob/master/doc/lzma-file-format.txt
+
+use std::convert::TryFrom;
+
+pub fn level_range() -> std::ops::RangeInclusive<i32> {
+    0..=9
+}
+
+fn quality_to_codec(quality: crate::Quality) -> u32 {
+    match quality {
+        crate::Quality::Default => 6,
+        crate::Quality::Level1 => 1,
+        crate::Quality::Level2 => 2,
+        crate::Quality::Level3 => 3,
+        crate::Quality::Level4 => 4,
+        crate::Quality::Level5 => 5,
+        crate::Quality::Level6 => 6,
+        crate::Quality::Level7 => 7,
+        crate::Quality::Level8 => 8,
+        crate::Quality::Level9 => 9,
+        crate::Quality::Maximum => 9,
+        crate::Quality::Exact(level) => {
+            let range = level_range();
+            level.clamp(*range.start(), *range.end()) as u32
+        }
+    }
+}
+
+/// The largest dictionary, in bytes, that decoders accept unless
+/// `XzOptions::dict_size` allows more. It is the largest that any preset
+/// encodes with.
+pub const MAX_DICT_SIZE: u32 = 64 << 20;
+
+/// The memory liblzma needs beyond the dictionary to decode, rounded up.
+const DECODER_OVERHEAD: u64 = 1 << 20;
+
+/// The memory limit of an LZMA decoder that accepts dictionaries of up to
+/// `dict_size` bytes.
+fn memlimit(dict_size: u32) -> u64 {
+    u64::from(dict_size) + DECODER_OVERHEAD
+}
+
+fn lzma_encoder_stream(quality: crate::Quality) -> std::io::Result<xz2::stream::Stream> {
+    use xz2::stream::{LzmaOptions, Stream};
+    let options = LzmaOptions::new_preset(quality_to_codec(quality))?;
+    Ok(Stream::new_lzma_encoder(&options)?)
+}
+
+fn lzma_decoder_stream(dict_size: u32) -> std::io::Result<xz2::stream::Stream> {
+    Ok(xz2::stream::Stream::new_lzma_decoder(memlimit(dict_size))?)
+}
+
+pub fn encode(data: &[u8], quality: crate::Quality) -> crate::Result<Vec<u8>> {
+    encode_with_options(data, &crate::XzOptions::new(quality))
+}
+
+/// Encodes an LZMA-alone stream, which has no room for the integrity check
+/// or the filters of `options`.
+pub fn encode_with_options(data: &[u8], options: &crate::XzOptions) -> crate::Result<Vec<u8>> {
+    if options.bcj.is_some() || options.delta.is_some() {
+        return Err(crate::Error::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the lzma format does not support bcj or delta filters",
+        )));
+    }
+    let lzma = lzma_options(options)?;
+    // SAFETY: `lzma` outlives the initialisation call, which copies it.
+    Ok(code(data, |stream| unsafe {
+        lzma_sys::lzma_alone_encoder(stream, &lzma)
+    })?)
+}
+
+pub fn decode(data: &[u8]) -> crate::Result<Vec<u8>> {
+    decode_with_options(data, &crate::XzOptions::default())
+}
+
+/// Decodes an LZMA-alone stream whose dictionary is at most
+/// `options.dict_size` bytes, or `MAX_DICT_SIZE` if that is not set.
+pub fn decode_with_options(data: &[u8], options: &crate::XzOptions) -> crate::Result<Vec<u8>> {
+    use std::io::Write;
+    let dict_size = options.dict_size.unwrap_or(MAX_DICT_SIZE);
+    let mut decoder =
+        xz2::write::XzDecoder::new_stream(Vec::new(), lzma_decoder_stream(dict_size)?);
+    decoder.write_all(data).map_err(corrupt)?;
+    decoder.finish().map_err(corrupt)
+}
+
+pub fn encode_raw2(data: &[u8], quality: crate::Quality) -> crate::Result<Vec<u8>> {
+    encode_raw2_with_options(data, &crate::XzOptions::new(quality))
+}
+
+/// Encodes a raw LZMA2 stream, after the bcj and delta filters of `options`.
+/// Raw streams have no integrity check.
+pub fn encode_raw2_with_options(data: &[u8], options: &crate::XzOptions) -> crate::Result<Vec<u8>> {
+    let filters = Filters::new(options, lzma_options(options)?);
+    // SAFETY: `filters` outlives the initialisation call, which copies it.
+    Ok(code(data, |stream| unsafe {
+        lzma_sys::lzma_raw_encoder(stream, filters.as_ptr())
+    })?)
+}
+
+pub fn decode_raw2(data: &[u8]) -> crate::Result<Vec<u8>> {
+    decode_raw2_with_options(data, &crate::XzOptions::default())
+}
+
+/// Decodes a raw LZMA2 stream encoded with the filters of `options` and a
+/// dictionary of up to `options.dict_size` bytes, or `MAX_DICT_SIZE` if that
+/// is not set.
+pub fn decode_raw2_with_options(data: &[u8], options: &crate::XzOptions) -> crate::Result<Vec<u8>> {
+    use std::io::Read;
+    // The dictionary never needs to be larger than the output, which the
+    // chunk headers give without decoding anything.
+    let dict_size = options.dict_size.unwrap_or(MAX_DICT_SIZE);
+    let dict_size = match decoded_len_raw2(data) {
+        Some(len) => dict_size.min(u32::try_from(len).unwrap_or(u32::MAX)),
+        None => lzma_sys::LZMA_DICT_SIZE_MIN,
+    };
+    let options = crate::XzOptions {
+        dict_size: Some(dict_size.max(lzma_sys::LZMA_DICT_SIZE_MIN)),
+        ..*options
+    };
+    let mut buf = Vec::with_capacity(data.len() * 2);
+    RawDecoder::new(data, &options)?
+        .read_to_end(&mut buf)
+        .map_err(|err| crate::Error::corrupt(crate::Codec::Lzma2Raw, err))?;
+    Ok(buf)
+}
+
+/// Adds up the decoded sizes in the chunk headers of a raw LZMA2 stream,
+/// returning `None` if the stream is malformed or truncated.
+pub fn decoded_len_raw2(data: &[u8]) -> Option<u64> {
+    let mut len = 0u64;
+    let mut rest = data;
+    loop {
+        let control = *rest.first()?;
+        let (decoded, header, packed) = match control {
+            0x00 => return Some(len),
+            // An uncompressed chunk, with or without a dictionary reset.
+            0x01 | 0x02 => {
+                let size = u16::from_be_bytes([*rest.get(1)?, *rest.get(2)?]) as usize + 1;
+                (size, 3, size)
+            }
+            0x03..=0x7f => return None,
+            // An LZMA chunk, whose properties follow when it resets them.
+            _ => {
+                let high = usize::from(control & 0x1f) << 16;
+                let low = u16::from_be_bytes([*rest.get(1)?, *rest.get(2)?]) as usize;
+                let packed = u16::from_be_bytes([*rest.get(3)?, *rest.get(4)?]) as usize + 1;
+                let header = if control >= 0xc0 { 6 } else { 5 };
+                (high + low + 1, header, packed)
+            }
+        };
+        rest = rest.get(header + packed..)?;
+        len += decoded as u64;
+    }
+}
+
+fn corrupt(err: std::io::Error) -> crate::Error {
+    crate::Error::corrupt(crate::Codec::Lzma, err)
+}
+
+/// Returns the LZMA settings of `preset`. xz2 does not expose raw streams or
+/// custom filter chains, so those drive liblzma directly.
+fn preset_options(preset: u32) -> std::io::Result<lzma_sys::lzma_options_lzma> {
+    // SAFETY: lzma_options_lzma is a plain C struct, and zero is a valid
+    // value for each of its fields before the preset fills them in.
+    let mut options: lzma_sys::lzma_options_lzma = unsafe { std::mem::zeroed() };
+    if unsafe { lzma_sys::lzma_lzma_preset(&mut options, preset) } != 0 {
+        return Err(lzma_error(lzma_sys::LZMA_OPTIONS_ERROR));
+    }
+    Ok(options)
+}
+
+/// Returns the LZMA settings of `options`, starting from its preset.
+pub fn lzma_options(options: &crate::XzOptions) -> std::io::Result<lzma_sys::lzma_options_lzma> {
+    let mut preset = quality_to_codec(options.quality);
+    if options.extreme {
+        preset |= lzma_sys::LZMA_PRESET_EXTREME;
+    }
+    let mut lzma = preset_options(preset)?;
+    lzma.dict_size = options.dict_size.unwrap_or(lzma.dict_size);
+    lzma.lc = options.literal_context_bits.unwrap_or(lzma.lc);
+    lzma.lp = options.literal_position_bits.unwrap_or(lzma.lp);
+    lzma.pb = options.position_bits.unwrap_or(lzma.pb);
+    lzma.nice_len = options.nice_len.unwrap_or(lzma.nice_len);
+    Ok(lzma)
+}
+
+/// The `lzma_options_delta` struct from liblzma's `delta.h`, which lzma-sys
+/// does not bind. `lzma_delta_type` is a C enum, so `u32` matches its ABI.
+#[repr(C)]
+struct DeltaOptions {
+    type_: u32,
+    dist: u32,
+    reserved_int: [u32; 4],
+    reserved_ptr: [*mut std::ffi::c_void; 2],
+}
+
+const LZMA_FILTER_DELTA: lzma_sys::lzma_vli = 0x03;
+const LZMA_DELTA_TYPE_BYTE: u32 = 0;
+
+// Six 32-bit fields followed by two pointers, as in `delta.h`.
+const _: () = assert!(
+    std::mem::size_of::<DeltaOptions>() == 6 * 4 + 2 * std::mem::size_of::<*mut u8>()
+        && std::mem::align_of::<DeltaOptions>() == std::mem::align_of::<*mut u8>()
+);
+
+/// The filter chain described by `XzOptions`: the optional bcj and delta
+/// filters followed by LZMA2. The filters point into the boxed options, so
+/// the chain stays valid when moved.
+pub struct Filters {
+    _lzma: Box<lzma_sys::lzma_options_lzma>,
+    _delta: Box<DeltaOptions>,
+    filters: Vec<lzma_sys::lzma_filter>,
+}
+
+impl Filters {
+    pub fn new(options: &crate::XzOptions, lzma: lzma_sys::lzma_options_lzma) -> Self {
+        use lzma_sys::*;
+        use std::ffi::c_void;
+
+        let mut lzma = Box::new(lzma);
+        let mut delta = Box::new(DeltaOptions {
+            type_: LZMA_DELTA_TYPE_BYTE,
+            dist: options.delta.unwrap_or(1),
+            reserved_int: [0; 4],
+            reserved_ptr: [std::ptr::null_mut(); 2],
+        });
+
+        let mut filters = Vec::with_capacity(4);
+        if let Some(bcj) = options.bcj {
+            let id = match bcj {
+                crate::XzBcj::X86 => LZMA_FILTER_X86,
+                crate::XzBcj::PowerPc => LZMA_FILTER_POWERPC,
+                crate::XzBcj::Ia64 => LZMA_FILTER_IA64,
+                crate::XzBcj::Arm => LZMA_FILTER_ARM,
+                crate::XzBcj::ArmThumb => LZMA_FILTER_ARMTHUMB,
+                crate::XzBcj::Sparc => LZMA_FILTER_SPARC,
+            };
+            filters.push(lzma_filter {
+                id,
+                options: std::ptr::null_mut(),
+            });
+        }
+        if options.delta.is_some() {
+            filters.push(lzma_filter {
+                id: LZMA_FILTER_DELTA,
+                options: &mut *delta as *mut DeltaOptions as *mut c_void,
+            });
+        }
+        filters.push(lzma_filter {
+            id: LZMA_FILTER_LZMA2,
+            options: &mut *lzma as *mut lzma_options_lzma as *mut c_void,
+        });
+        filters.push(lzma_filter {
+            id: LZMA_VLI_UNKNOWN,
+            options: std::ptr::null_mut(),
+        });
+
+        Filters {
+            _lzma: lzma,
+            _delta: delta,
+            filters,
+        }
+    }
+
+    pub fn as_ptr(&self) -> *const lzma_sys::lzma_filter {
+        self.filters.as_ptr()
+    }
+}
+
+/// Initialises a liblzma stream with `init` and runs it over all of `data`.
+pub fn code<F>(data: &[u8], init: F) -> std::io::Result<Vec<u8>>
+where
+    F: FnOnce(&mut lzma_sys::lzma_stream) -> lzma_sys::lzma_ret,
+{
+    use lzma_sys::*;
+
+    // SAFETY: a zeroed lzma_stream is equivalent to LZMA_STREAM_INIT.
+    let mut stream: lzma_stream = unsafe { std::mem::zeroed() };
+    let ret = init(&mut stream);
+    if ret != LZMA_OK {
+        unsafe { lzma_end(&mut stream) };
+        return Err(lzma_error(ret));
+    }
+
+    let mut buf: Vec<u8> = Vec::with_capacity(data.len() / 2 + 64);
+    stream.next_in = data.as_ptr();
+    stream.avail_in = data.len();
+    let ret = loop {
+        if buf.len() == buf.capacity() {
+            buf.reserve(buf.capacity().max(4096));
+        }
+        let len = buf.len();
+        let available = buf.capacity() - len;
+        // SAFETY: liblzma writes at most `avail_out` bytes to the spare
+        // capacity of `buf`, and reports how many through `avail_out`.
+        let ret = unsafe {
+            stream.next_out = buf.as_mut_ptr().add(len);
+            stream.avail_out = available;
+            let ret = lzma_code(&mut stream, LZMA_FINISH);
+            buf.set_len(len + available - stream.avail_out);
+            ret
+        };
+        if ret != LZMA_OK {
+            break ret;
+        }
+    };
+    let trailing = stream.avail_in;
+    // SAFETY: the stream was initialised above and is not used afterwards.
+    unsafe { lzma_end(&mut stream) };
+
+    match ret {
+        LZMA_STREAM_END if trailing == 0 => Ok(buf),
+        LZMA_STREAM_END => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "trailing data after the end of the lzma stream",
+        )),
+        ret => Err(lzma_error(ret)),
+    }
+}
+
+pub fn lzma_error(ret: lzma_sys::lzma_ret) -> std::io::Error {
+    use std::io::{Error, ErrorKind};
+    match ret {
+        lzma_sys::LZMA_MEM_ERROR => Error::new(ErrorKind::OutOfMemory, "out of memory"),
+        lzma_sys::LZMA_OPTIONS_ERROR => Error::new(ErrorKind::InvalidInput, "invalid options"),
+        lzma_sys::LZMA_DATA_ERROR | lzma_sys::LZMA_FORMAT_ERROR => {
+            Error::new(ErrorKind::InvalidData, "corrupt data")
+        }
+        lzma_sys::LZMA_BUF_ERROR => Error::new(ErrorKind::UnexpectedEof, "truncated data"),
+        ret => Error::other(format!("unexpected liblzma error {}", ret)),
+    }
+}
+
+/// Decodes a raw LZMA2 stream as it is read, so callers that stop reading
+/// early never decode more than they asked for.
+pub struct RawDecoder<R> {
+    stream: lzma_sys::lzma_stream,
+    input: Box<[u8]>,
+    pos: usize,
+    len: usize,
+    eof: bool,
+    done: bool,
+    reader: R,
+}
+
+// SAFETY: the stream is only accessed through `&mut self`, and liblzma keeps
+// no thread-local state, as in xz2's `Stream`.
+unsafe impl<R: Send> Send for RawDecoder<R> {}
+unsafe impl<R: Sync> Sync for RawDecoder<R> {}
+
+impl<R: std::io::Read> RawDecoder<R> {
+    /// Creates a decoder for streams encoded with the filters of `options`
+    /// and a dictionary of up to `options.dict_size` bytes, or
+    /// `MAX_DICT_SIZE` if that is not set.
+    pub fn new(reader: R, options: &crate::XzOptions) -> std::io::Result<Self> {
+        let mut lzma = preset_options(0)?;
+        lzma.dict_size = options.dict_size.unwrap_or(MAX_DICT_SIZE);
+        let filters = Filters::new(options, lzma);
+        // SAFETY: a zeroed lzma_stream is equivalent to LZMA_STREAM_INIT, and
+        // `filters` outlives the initialisation call, which copies it.
+        let mut stream: lzma_sys::lzma_stream = unsafe { std::mem::zeroed() };
+        let ret = unsafe { lzma_sys::lzma_raw_decoder(&mut stream, filters.as_ptr()) };
+        if ret != lzma_sys::LZMA_OK {
+            unsafe { lzma_sys::lzma_end(&mut stream) };
+            return Err(lzma_error(ret));
+        }
+        Ok(RawDecoder {
+            stream,
+            input: vec![0; 32 * 1024].into_boxed_slice(),
+            pos: 0,
+            len: 0,
+            eof: false,
+            done: false,
+            reader,
+        })
+    }
+
+    /// Fails if anything follows the end of the stream.
+    fn check_trailing(&mut self) -> std::io::Result<()> {
+        let mut byte = [0u8];
+        if self.pos < self.len || self.reader.read(&mut byte)? > 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "trailing data after the end of the lzma2 stream",
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl<R: std::io::Read> std::io::Read for RawDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        use lzma_sys::*;
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            if self.pos == self.len && !self.eof {
+                self.len = self.reader.read(&mut self.input)?;
+                self.pos = 0;
+                self.eof = self.len == 0;
+            }
+            let action = if self.eof { LZMA_FINISH } else { LZMA_RUN };
+
+            let available = self.len - self.pos;
+            self.stream.next_in = self.input[self.pos..].as_ptr();
+            self.stream.avail_in = available;
+            self.stream.next_out = buf.as_mut_ptr();
+            self.stream.avail_out = buf.len();
+            // SAFETY: liblzma reads at most `avail_in` bytes of `input` and
+            // writes at most `avail_out` bytes to `buf`.
+            let ret = unsafe { lzma_code(&mut self.stream, action) };
+            self.pos += available - self.stream.avail_in;
+            let written = buf.len() - self.stream.avail_out;
+
+            match ret {
+                LZMA_OK if written > 0 => return Ok(written),
+                LZMA_OK => {}
+                LZMA_STREAM_END => {
+                    self.done = true;
+                    self.check_trailing()?;
+                    return Ok(written);
+                }
+                ret => return Err(lzma_error(ret)),
+            }
+        }
+    }
+}
+
+impl<R> Drop for RawDecoder<R> {
+    fn drop(&mut self) {
+        // SAFETY: the stream was initialised in `new`.
+        unsafe { lzma_sys::lzma_end(&mut self.stream) };
+    }
+}
+
+pub fn encoder<W: std::io::Write>(
+    writer: W,
+    quality: crate::Quality,
+) -> std::io::Result<xz2::write::XzEncoder<W>> {
+    Ok(xz2::write::XzEncoder::new_stream(
+        writer,
+        lzma_encoder_stream(quality)?,
+    ))
+}
+
+pub fn decoder<R: std::io::Read>(reader: R) -> std::io::Result<xz2::read::XzDecoder<R>> {
+    Ok(xz2::read::XzDecoder::new_stream(
+        reader,
+        lzma_decoder_stream(MAX_DICT_SIZE)?,
+    ))
+}
+
+#[cfg(feature = "async")]
+pub fn write_decoder<W: std::io::Write>(writer: W) -> std::io::Result<xz2::write::XzDecoder<W>> {
+    Ok(xz2::write::XzDecoder::new_stream(
+        writer,
+        lzma_decoder_stream(MAX_DICT_SIZE)?,
+    ))
+}
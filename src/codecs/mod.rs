@@ -1,21 +1,39 @@
+#[cfg(feature = "base32_support")]
+pub mod base32;
+
 #[cfg(feature = "base58_support")]
 pub mod base58;
 
+#[cfg(feature = "base64_support")]
+pub mod base64;
+
 #[cfg(feature = "bincode_support")]
 pub mod bincode;
 
 #[cfg(feature = "brotli_support")]
 pub mod brotli;
 
+#[cfg(feature = "bzip2_support")]
+pub mod bzip2;
+
 #[cfg(feature = "deflate_support")]
 pub mod deflate;
 
 #[cfg(feature = "gzip_support")]
 pub mod gzip;
 
+#[cfg(feature = "hex_support")]
+pub mod hex;
+
 #[cfg(feature = "lz4_support")]
 pub mod lz4;
 
+#[cfg(feature = "xz_support")]
+pub mod lzma;
+
+#[cfg(feature = "snappy_support")]
+pub mod snappy;
+
 #[cfg(feature = "xz_support")]
 pub mod xz;
 
@@ -24,3 +42,17 @@ pub mod zlib;
 
 #[cfg(feature = "zstd_support")]
 pub mod zstd;
+
+/// Removes ASCII whitespace, which the text codecs ignore so that wrapped
+/// lines (e.g. from MIME) decode.
+#[cfg(any(
+    feature = "base32_support",
+    feature = "base64_support",
+    feature = "hex_support"
+))]
+fn strip_whitespace(data: &[u8]) -> Vec<u8> {
+    data.iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect()
+}
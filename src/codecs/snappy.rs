@@ -0,0 +1,123 @@
+// https://github.com/google/snappy/blob/master/format_description.txt
+// https://github.com/google/snappy/blob/master/framing_format.txt
+
+pub fn encode(data: &[u8], _: crate::Quality) -> crate::Result<Vec<u8>> {
+    use std::io::Write;
+    let mut encoder = snap::write::FrameEncoder::new(Vec::new());
+    encoder.write_all(data)?;
+    encoder
+        .into_inner()
+        .map_err(|err| crate::Error::Io(err.into_error()))
+}
+
+pub fn decode(data: &[u8]) -> crate::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut buf = Vec::new();
+    snap::read::FrameDecoder::new(data)
+        .read_to_end(&mut buf)
+        .map_err(|err| crate::Error::corrupt(crate::Codec::Snappy, err))?;
+    Ok(buf)
+}
+
+pub fn encode_raw(data: &[u8], _: crate::Quality) -> crate::Result<Vec<u8>> {
+    snap::raw::Encoder::new()
+        .compress_vec(data)
+        .map_err(|err| crate::Error::Io(err.into()))
+}
+
+pub fn decode_raw(data: &[u8]) -> crate::Result<Vec<u8>> {
+    snap::raw::Decoder::new()
+        .decompress_vec(data)
+        .map_err(|err| crate::Error::corrupt(crate::Codec::SnappyRaw, err))
+}
+
+/// Reads the decompressed length stored at the start of a raw snappy block.
+pub fn decoded_len_raw(data: &[u8]) -> Option<u64> {
+    snap::raw::decompress_len(data).ok().map(|len| len as u64)
+}
+
+pub fn encoder<W: std::io::Write>(
+    writer: W,
+    _: crate::Quality,
+) -> std::io::Result<snap::write::FrameEncoder<W>> {
+    Ok(snap::write::FrameEncoder::new(writer))
+}
+
+pub fn decoder<R: std::io::Read>(reader: R) -> std::io::Result<snap::read::FrameDecoder<R>> {
+    Ok(snap::read::FrameDecoder::new(reader))
+}
+
+#[cfg(feature = "async")]
+pub fn write_decoder<W: std::io::Write>(writer: W) -> std::io::Result<WriteDecoder<W>> {
+    Ok(WriteDecoder {
+        buffer: Vec::new(),
+        started: false,
+        writer,
+    })
+}
+
+/// The stream identifier chunk that starts every snappy frame stream.
+#[cfg(feature = "async")]
+const STREAM_IDENTIFIER: &[u8] = b"\xff\x06\x00\x00sNaPpY";
+
+/// Decodes a snappy frame stream as it is written, one chunk at a time. snap
+/// only provides a read-side frame decoder, so complete chunks are handed to
+/// it and only a partial trailing chunk is kept in memory.
+#[cfg(feature = "async")]
+pub struct WriteDecoder<W> {
+    buffer: Vec<u8>,
+    /// Whether the stream identifier has been decoded, after which it is
+    /// prepended to each batch of chunks.
+    started: bool,
+    writer: W,
+}
+
+#[cfg(feature = "async")]
+impl<W: std::io::Write> WriteDecoder<W> {
+    /// Writes out any remaining data, returning the inner writer.
+    pub fn finish(self) -> std::io::Result<W> {
+        match self.buffer.is_empty() {
+            true => Ok(self.writer),
+            false => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "truncated snappy frame",
+            )),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<W: std::io::Write> std::io::Write for WriteDecoder<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        use std::io::Read;
+        self.buffer.extend_from_slice(buf);
+
+        // Each chunk has a one byte type and a three byte little endian length.
+        let mut end = 0;
+        while let Some(header) = self.buffer.get(end..end + 4) {
+            let len =
+                usize::from(header[1]) | usize::from(header[2]) << 8 | usize::from(header[3]) << 16;
+            if self.buffer.len() < end + 4 + len {
+                break;
+            }
+            end += 4 + len;
+        }
+        if end == 0 {
+            return Ok(buf.len());
+        }
+
+        let prefix: &[u8] = match self.started {
+            true => STREAM_IDENTIFIER,
+            false => &[],
+        };
+        let chunks = self.buffer.drain(..end);
+        let mut decoder = snap::read::FrameDecoder::new(prefix.chain(chunks.as_slice()));
+        std::io::copy(&mut decoder, &mut self.writer)?;
+        self.started = true;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -1,3 +1,7 @@
+pub fn level_range() -> std::ops::RangeInclusive<i32> {
+    0..=9
+}
+
 fn quality_to_codec(quality: crate::Quality) -> u32 {
     match quality {
         crate::Quality::Default => 6,
@@ -11,73 +15,67 @@ fn quality_to_codec(quality: crate::Quality) -> u32 {
         crate::Quality::Level8 => 8,
         crate::Quality::Level9 => 9,
         crate::Quality::Maximum => 9,
+        crate::Quality::Exact(level) => {
+            let range = level_range();
+            level.clamp(*range.start(), *range.end()) as u32
+        }
     }
 }
 
-pub fn encode(data: &[u8], quality: crate::Quality) -> std::io::Result<Vec<u8>> {
+pub fn encode(data: &[u8], quality: crate::Quality) -> crate::Result<Vec<u8>> {
     use std::io::Write;
     let mut buf = Vec::new();
     let mut encoder = xz2::write::XzEncoder::new(buf, quality_to_codec(quality));
-    encoder.write_all(&data[..])?;
+    encoder.write_all(data)?;
     buf = encoder.finish()?;
     Ok(buf)
 }
 
-pub fn decode(data: &[u8]) -> std::io::Result<Vec<u8>> {
-    use std::io::Write;
-    let mut buf = Vec::new();
-    let mut decoder = xz2::write::XzDecoder::new(buf);
-    decoder.write_all(&data[..])?;
-    buf = decoder.finish()?;
-    Ok(buf)
-}
-
-/*
+pub fn encode_with_options(data: &[u8], options: &crate::XzOptions) -> crate::Result<Vec<u8>> {
+    use crate::codecs::lzma::{code, lzma_options, Filters};
+    use lzma_sys::*;
 
-// Old code from native rust lzma-rs (lzma2 doesn't seem to compress correctly, and neither variants expose compression level..)
+    let filters = Filters::new(options, lzma_options(options)?);
+    let check = match options.check {
+        crate::XzCheck::None => LZMA_CHECK_NONE,
+        crate::XzCheck::Crc32 => LZMA_CHECK_CRC32,
+        crate::XzCheck::Crc64 => LZMA_CHECK_CRC64,
+        crate::XzCheck::Sha256 => LZMA_CHECK_SHA256,
+    };
+    // SAFETY: `filters` outlives the initialisation call, which copies it.
+    Ok(code(data, |stream| unsafe {
+        lzma_stream_encoder(stream, filters.as_ptr(), check)
+    })?)
+}
 
-pub fn encode(data: &[u8], _quality: crate::Quality) -> std::io::Result<Vec<u8>> {
+pub fn decode(data: &[u8]) -> crate::Result<Vec<u8>> {
+    use std::io::Write;
     let mut buf = Vec::new();
-    lzma_rs::lzma_compress(&mut std::io::Cursor::new(data), &mut buf).map_err(|err| {
-        std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("failed to encode with lzma - details: {:?}", err),
-        )
-    })?;
+    let mut decoder = xz2::write::XzDecoder::new_multi_decoder(buf);
+    decoder.write_all(data).map_err(corrupt)?;
+    buf = decoder.finish().map_err(corrupt)?;
     Ok(buf)
 }
 
-pub fn decode(data: &[u8]) -> std::io::Result<Vec<u8>> {
-    let mut buf = Vec::new();
-    lzma_rs::lzma_decompress(&mut std::io::Cursor::new(data), &mut buf).map_err(|err| {
-        std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("failed to decode with lzma - details: {:?}", err),
-        )
-    })?;
-    Ok(buf)
+fn corrupt(err: std::io::Error) -> crate::Error {
+    crate::Error::corrupt(crate::Codec::Xz, err)
 }
 
-pub fn encode(data: &[u8], _quality: crate::Quality) -> std::io::Result<Vec<u8>> {
-    let mut buf = Vec::new();
-    lzma_rs::lzma2_compress(&mut std::io::Cursor::new(data), &mut buf).map_err(|err| {
-        std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("failed to encode with lzma2 - details: {:?}", err),
-        )
-    })?;
-    Ok(buf)
+pub fn encoder<W: std::io::Write>(
+    writer: W,
+    quality: crate::Quality,
+) -> std::io::Result<xz2::write::XzEncoder<W>> {
+    Ok(xz2::write::XzEncoder::new(
+        writer,
+        quality_to_codec(quality),
+    ))
 }
 
-pub fn decode(data: &[u8]) -> std::io::Result<Vec<u8>> {
-    let mut buf = Vec::new();
-    lzma_rs::lzma2_decompress(&mut std::io::Cursor::new(data), &mut buf).map_err(|err| {
-        std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("failed to decode with lzma2 - details: {:?}", err),
-        )
-    })?;
-    Ok(buf)
+pub fn decoder<R: std::io::Read>(reader: R) -> std::io::Result<xz2::read::XzDecoder<R>> {
+    Ok(xz2::read::XzDecoder::new_multi_decoder(reader))
 }
 
-*/
+#[cfg(feature = "async")]
+pub fn write_decoder<W: std::io::Write>(writer: W) -> std::io::Result<xz2::write::XzDecoder<W>> {
+    Ok(xz2::write::XzDecoder::new_multi_decoder(writer))
+}
@@ -1,3 +1,7 @@
+pub fn level_range() -> std::ops::RangeInclusive<i32> {
+    zstd_safe::min_c_level()..=zstd_safe::max_c_level()
+}
+
 fn quality_to_codec(quality: crate::Quality) -> i32 {
     match quality {
         crate::Quality::Default => 0,
@@ -11,32 +15,126 @@ fn quality_to_codec(quality: crate::Quality) -> i32 {
         crate::Quality::Level8 => 8,
         crate::Quality::Level9 => 9,
         crate::Quality::Maximum => 11, // Goes to 21, but significantly more time (i.e. ~164ms vs 0.5ms at default)
+        crate::Quality::Exact(level) => {
+            let range = level_range();
+            level.clamp(*range.start(), *range.end())
+        }
     }
 }
 
-pub fn encode(data: &[u8], quality: crate::Quality) -> std::io::Result<Vec<u8>> {
+pub fn encode(data: &[u8], quality: crate::Quality) -> crate::Result<Vec<u8>> {
     let mut buf = Vec::new();
     let mut writer = std::io::Cursor::new(&mut buf);
     let mut encoder = zstd::stream::Encoder::new(&mut writer, quality_to_codec(quality))?;
     std::io::copy(&mut std::io::Cursor::new(data), &mut encoder)?;
-    encoder.finish().map_err(|err| {
-        std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("failed to encode with zstd - details: {:?}", err),
-        )
-    })?;
+    encoder.finish()?;
+    Ok(buf)
+}
+
+pub fn encode_with_options(data: &[u8], options: &crate::ZstdOptions) -> crate::Result<Vec<u8>> {
+    use zstd_safe::CParameter;
+    let mut parameters = vec![
+        CParameter::CompressionLevel(quality_to_codec(options.quality)),
+        CParameter::EnableLongDistanceMatching(options.long_distance_matching),
+        CParameter::ChecksumFlag(options.checksum),
+    ];
+    if let Some(window_log) = options.window_log {
+        parameters.push(CParameter::WindowLog(window_log));
+    }
+    let mut context = zstd_safe::create_cctx();
+    for parameter in parameters {
+        zstd_safe::cctx_set_parameter(&mut context, parameter).map_err(invalid_input)?;
+    }
+
+    // Compressing in one pass records the content size in the frame header,
+    // so small inputs are not decoded with a window larger than themselves.
+    let mut buf = vec![0; zstd_safe::compress_bound(data.len())];
+    let len = zstd_safe::compress2(&mut context, &mut buf, data).map_err(invalid_input)?;
+    buf.truncate(len);
     Ok(buf)
 }
 
-pub fn decode(data: &[u8]) -> std::io::Result<Vec<u8>> {
+/// The largest window log that decoders accept unless configured otherwise.
+const WINDOW_LOG_LIMIT_DEFAULT: u32 = 27;
+
+/// Decodes `data`, accepting frames with windows of up to `2^window_log`
+/// bytes when `window_log` is larger than the default limit.
+pub fn decode_with_options(data: &[u8], options: &crate::ZstdOptions) -> crate::Result<Vec<u8>> {
+    use std::io::Write;
+    let mut decoder = zstd::stream::raw::Decoder::new()?;
+    if let Some(window_log) = options.window_log {
+        decoder.set_parameter(zstd::stream::raw::DParameter::WindowLogMax(
+            window_log.max(WINDOW_LOG_LIMIT_DEFAULT),
+        ))?;
+    }
+    let mut writer = zstd::stream::zio::Writer::new(Vec::new(), decoder);
+    writer.write_all(data).map_err(corrupt)?;
+    writer.finish().map_err(corrupt)?;
+    Ok(writer.into_inner().0)
+}
+
+fn invalid_input(code: usize) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        zstd_safe::get_error_name(code),
+    )
+}
+
+pub fn decode(data: &[u8]) -> crate::Result<Vec<u8>> {
     let mut buf = Vec::new();
     let mut reader = std::io::Cursor::new(data);
     let mut writer = std::io::Cursor::new(&mut buf);
-    zstd::stream::copy_decode(&mut reader, &mut writer).map_err(|err| {
-        std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("failed to decode with zstd - details: {:?}", err),
-        )
-    })?;
+    zstd::stream::copy_decode(&mut reader, &mut writer).map_err(corrupt)?;
     Ok(buf)
 }
+
+pub fn encode_with_dictionary(
+    data: &[u8],
+    quality: crate::Quality,
+    dictionary: &[u8],
+) -> crate::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    let mut encoder =
+        zstd::stream::Encoder::with_dictionary(&mut buf, quality_to_codec(quality), dictionary)?;
+    std::io::copy(&mut std::io::Cursor::new(data), &mut encoder)?;
+    encoder.finish()?;
+    Ok(buf)
+}
+
+pub fn decode_with_dictionary(data: &[u8], dictionary: &[u8]) -> crate::Result<Vec<u8>> {
+    use std::io::Read;
+    let mut buf = Vec::new();
+    zstd::stream::Decoder::with_dictionary(std::io::BufReader::new(data), dictionary)
+        .and_then(|mut decoder| decoder.read_to_end(&mut buf))
+        .map_err(corrupt)?;
+    Ok(buf)
+}
+
+fn corrupt(err: std::io::Error) -> crate::Error {
+    crate::Error::corrupt(crate::Codec::Zstd, err)
+}
+
+pub fn encoder<W: std::io::Write>(
+    writer: W,
+    quality: crate::Quality,
+) -> std::io::Result<zstd::stream::write::Encoder<W>> {
+    zstd::stream::write::Encoder::new(writer, quality_to_codec(quality))
+}
+
+pub fn decoder<R: std::io::Read>(
+    reader: R,
+) -> std::io::Result<zstd::stream::read::Decoder<std::io::BufReader<R>>> {
+    zstd::stream::read::Decoder::new(reader)
+}
+
+#[cfg(feature = "async")]
+pub fn write_decoder<W: std::io::Write>(
+    writer: W,
+) -> std::io::Result<zstd::stream::zio::Writer<W, zstd::stream::raw::Decoder>> {
+    // Unlike `write::Decoder`, `zio::Writer` can be finished, which reports
+    // input that ends partway through a frame.
+    Ok(zstd::stream::zio::Writer::new(
+        writer,
+        zstd::stream::raw::Decoder::new()?,
+    ))
+}
@@ -0,0 +1,251 @@
+use crate::{Codec, Error};
+#[cfg(any(feature = "brotli_support", feature = "deflate_support"))]
+use std::io;
+
+/// Candidates below this confidence are never returned by `detect`.
+const DETECT_THRESHOLD: f32 = 0.5;
+
+/// How much decoded output the trial decoders produce before giving up.
+#[cfg(any(feature = "brotli_support", feature = "deflate_support"))]
+const TRIAL_OUTPUT_LEN: usize = 64 * 1024;
+
+/// A codec that some data may have been encoded with.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Detection {
+    pub codec: Codec,
+
+    /// How likely the data is to be encoded with `codec`, from 0 to 1.
+    pub confidence: f32,
+}
+
+/// Returns true for codecs without a signature, which are only ever reported
+/// by `detect_candidates`.
+fn is_ambiguous(codec: Codec) -> bool {
+    matches!(codec, Codec::Deflate | Codec::BinCode | Codec::Base58)
+}
+
+/// Identifies the codec used to produce `data`.
+///
+/// Gzip, zlib, zstd, bzip2, lz4, framed snappy, xz and lzma are recognised by
+/// their magic bytes, and brotli by trial decoding. Raw deflate, bincode and base58
+/// have no reliable signature, so they are never returned here; use
+/// `detect_candidates` to see how plausible they are. Raw snappy is never
+/// detected.
+pub fn detect(data: &[u8]) -> Option<Codec> {
+    detect_candidates(data)
+        .into_iter()
+        .find(|candidate| {
+            !is_ambiguous(candidate.codec) && candidate.confidence >= DETECT_THRESHOLD
+        })
+        .map(|candidate| candidate.codec)
+}
+
+/// Returns every codec that `data` could plausibly be encoded with, most
+/// likely first.
+pub fn detect_candidates(data: &[u8]) -> Vec<Detection> {
+    let mut candidates = Vec::new();
+    let mut push = |codec, confidence| candidates.push(Detection { codec, confidence });
+
+    if data.starts_with(&[0x1f, 0x8b, 0x08]) {
+        push(Codec::Gzip, 1.0);
+    }
+    if data.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        push(Codec::Zstd, 1.0);
+    }
+    if data.starts_with(&[0x04, 0x22, 0x4d, 0x18]) {
+        push(Codec::Lz4, 1.0);
+    }
+    if is_bzip2_header(data) {
+        push(Codec::Bzip2, 1.0);
+    }
+    if data.starts_with(b"\xff\x06\x00\x00sNaPpY") {
+        push(Codec::Snappy, 1.0);
+    }
+    if data.starts_with(&[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00]) {
+        push(Codec::Xz, 1.0);
+    }
+    if is_lzma_header(data) {
+        push(Codec::Lzma, 0.8);
+    }
+    if is_zlib_header(data) {
+        push(Codec::Zlib, 0.9);
+    }
+    if let Some(confidence) = brotli_confidence(data) {
+        push(Codec::Brotli, confidence);
+    }
+    if let Some(confidence) = deflate_confidence(data) {
+        push(Codec::Deflate, confidence);
+    }
+    if is_bincode_prefix(data) {
+        push(Codec::BinCode, 0.5);
+    }
+    if is_base58_text(data) {
+        push(Codec::Base58, 0.4);
+    }
+
+    candidates.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    candidates
+}
+
+/// Detects the codec used to produce `data` and decodes it.
+///
+/// Data wrapped in a `frame` is decoded using the codec recorded in its header.
+pub fn decode_auto(data: &[u8]) -> crate::Result<Vec<u8>> {
+    if crate::frame::is_framed(data) {
+        return crate::frame::decode(data);
+    }
+    match detect(data) {
+        Some(codec) => crate::decode(data, codec),
+        None => Err(Error::Undetected),
+    }
+}
+
+/// Checks for a bzip2 stream header followed by a block or end of stream marker.
+fn is_bzip2_header(data: &[u8]) -> bool {
+    match data {
+        [b'B', b'Z', b'h', level, magic @ ..] if (b'1'..=b'9').contains(level) => {
+            magic.starts_with(&[0x31, 0x41, 0x59, 0x26, 0x53, 0x59])
+                || magic.starts_with(&[0x17, 0x72, 0x45, 0x38, 0x50, 0x90])
+        }
+        _ => false,
+    }
+}
+
+/// Checks for an LZMA-alone header with the default literal and position
+/// properties, which every common encoder writes, and a dictionary size that
+/// is a power of two.
+fn is_lzma_header(data: &[u8]) -> bool {
+    match data {
+        [0x5d, dict @ .., _, _, _, _, _, _, _, _] if dict.len() >= 4 => {
+            let dict_size = u32::from_le_bytes([dict[0], dict[1], dict[2], dict[3]]);
+            dict_size >= 4096 && dict_size.is_power_of_two()
+        }
+        _ => false,
+    }
+}
+
+/// Checks for a zlib header (RFC 1950) using deflate with a valid window size.
+fn is_zlib_header(data: &[u8]) -> bool {
+    match data {
+        [cmf, flg, ..] => {
+            cmf & 0x0f == 8 && cmf >> 4 <= 7 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0
+        }
+        _ => false,
+    }
+}
+
+/// Checks for the little-endian `u64` length prefix bincode writes.
+fn is_bincode_prefix(data: &[u8]) -> bool {
+    if data.len() < 8 {
+        return false;
+    }
+    let mut len = [0u8; 8];
+    len.copy_from_slice(&data[..8]);
+    u64::from_le_bytes(len) == (data.len() - 8) as u64
+}
+
+/// Checks that `data` only contains characters from the bitcoin base58
+/// alphabet, ignoring surrounding whitespace.
+fn is_base58_text(data: &[u8]) -> bool {
+    const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    let text = data.trim_ascii();
+    !text.is_empty() && text.iter().all(|byte| ALPHABET.contains(byte))
+}
+
+/// Brotli has no magic bytes, so the best we can do is try to decode a prefix.
+#[cfg(feature = "brotli_support")]
+fn brotli_confidence(data: &[u8]) -> Option<f32> {
+    trial_decode(brotli::Decompressor::new(data, 4096), 0.8, 0.6)
+}
+
+#[cfg(not(feature = "brotli_support"))]
+fn brotli_confidence(_: &[u8]) -> Option<f32> {
+    None
+}
+
+#[cfg(feature = "deflate_support")]
+fn deflate_confidence(data: &[u8]) -> Option<f32> {
+    trial_decode(flate2::read::DeflateDecoder::new(data), 0.4, 0.2)
+}
+
+#[cfg(not(feature = "deflate_support"))]
+fn deflate_confidence(_: &[u8]) -> Option<f32> {
+    None
+}
+
+/// Decodes up to `TRIAL_OUTPUT_LEN` bytes, returning `complete` if the whole
+/// stream decoded cleanly or `partial` if the prefix decoded without errors.
+#[cfg(any(feature = "brotli_support", feature = "deflate_support"))]
+fn trial_decode<R: io::Read>(decoder: R, complete: f32, partial: f32) -> Option<f32> {
+    use std::io::Read;
+    let mut buf = Vec::new();
+    match decoder
+        .take(TRIAL_OUTPUT_LEN as u64 + 1)
+        .read_to_end(&mut buf)
+    {
+        Ok(len) if len > TRIAL_OUTPUT_LEN => Some(partial),
+        Ok(len) if len > 0 => Some(complete),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const TEST_DATA: &[u8] = include_bytes!("ipsum.txt");
+
+    fn detect_encoded(codec: Codec) -> Option<Codec> {
+        detect(&encode(TEST_DATA, codec, Quality::Default).unwrap())
+    }
+
+    #[test]
+    fn detect_signatures() {
+        for &codec in &[
+            Codec::Gzip,
+            Codec::Zlib,
+            Codec::Zstd,
+            Codec::Lz4,
+            Codec::Xz,
+            Codec::Lzma,
+        ] {
+            if is_codec_enabled(codec) {
+                assert_eq!(detect_encoded(codec), Some(codec));
+            }
+        }
+        assert_eq!(detect(TEST_DATA), None);
+        assert_eq!(detect(&[]), None);
+    }
+
+    #[cfg(feature = "brotli_support")]
+    #[test]
+    fn detect_brotli() {
+        assert_eq!(detect_encoded(Codec::Brotli), Some(Codec::Brotli));
+    }
+
+    #[test]
+    fn detect_ambiguous() {
+        for &codec in &[Codec::Deflate, Codec::BinCode, Codec::Base58] {
+            if is_codec_enabled(codec) {
+                let encoded = encode(TEST_DATA, codec, Quality::Default).unwrap();
+                assert_eq!(detect(&encoded), None);
+                assert!(detect_candidates(&encoded)
+                    .iter()
+                    .any(|candidate| candidate.codec == codec && candidate.confidence < 1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn decode_auto_round_trip() {
+        for &codec in &[Codec::Gzip, Codec::Zstd, Codec::Xz, Codec::Brotli] {
+            if is_codec_enabled(codec) {
+                let encoded = encode(TEST_DATA, codec, Quality::Default).unwrap();
+                assert_eq!(decode_auto(&encoded).unwrap(), TEST_DATA);
+            }
+        }
+        let framed = encode_framed(TEST_DATA, Codec::Identity, Quality::Default).unwrap();
+        assert_eq!(decode_auto(&framed).unwrap(), TEST_DATA);
+        assert!(decode_auto(TEST_DATA).is_err());
+    }
+}
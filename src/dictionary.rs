@@ -0,0 +1,111 @@
+use crate::{codecs, Error, Quality, Result};
+
+/// A zstd dictionary, used to improve the compression of small inputs that
+/// share content with the samples it was trained on.
+///
+/// Frames encoded with a trained dictionary record its id, so decoding with
+/// the wrong dictionary fails with `Error::DictionaryMismatch`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Dictionary {
+    data: Vec<u8>,
+    id: u32,
+}
+
+impl Dictionary {
+    /// Trains a dictionary of at most `max_size` bytes from sample buffers.
+    pub fn train<S: AsRef<[u8]>>(samples: &[S], max_size: usize) -> Result<Self> {
+        Ok(Dictionary::from_bytes(zstd::dict::from_samples(
+            samples, max_size,
+        )?))
+    }
+
+    /// Wraps a dictionary that was previously trained or loaded from disk.
+    ///
+    /// Any buffer can be used as a raw content dictionary, in which case the
+    /// id is zero and is not checked when decoding.
+    pub fn from_bytes(data: Vec<u8>) -> Self {
+        let id = zstd_safe::get_dict_id_from_dict(&data);
+        Dictionary { data, id }
+    }
+
+    /// The id stored in the dictionary, or zero for raw content dictionaries.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Encodes `data` with zstd using `dictionary`.
+pub fn encode_with_dictionary(
+    data: &[u8],
+    quality: Quality,
+    dictionary: &Dictionary,
+) -> Result<Vec<u8>> {
+    codecs::zstd::encode_with_dictionary(data, quality, dictionary.as_bytes())
+}
+
+/// Decodes zstd `data` that was encoded using `dictionary`.
+pub fn decode_with_dictionary(data: &[u8], dictionary: &Dictionary) -> Result<Vec<u8>> {
+    let frame_id = zstd_safe::get_dict_id_from_frame(data);
+    if frame_id != 0 && frame_id != dictionary.id() {
+        return Err(Error::DictionaryMismatch {
+            expected: frame_id,
+            found: dictionary.id(),
+        });
+    }
+    codecs::zstd::decode_with_dictionary(data, dictionary.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn samples() -> Vec<Vec<u8>> {
+        (0..1000)
+            .map(|i| {
+                format!(
+                    r#"{{"id":{},"name":"user{}","email":"user{}@example.com","active":{}}}"#,
+                    i,
+                    i,
+                    i,
+                    i % 2 == 0
+                )
+                .into_bytes()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dictionary_round_trip() {
+        let samples = samples();
+        let dictionary = Dictionary::train(&samples, 4096).unwrap();
+        assert_ne!(dictionary.id(), 0);
+
+        let record = &samples[42];
+        let encoded = encode_with_dictionary(record, Quality::Default, &dictionary).unwrap();
+        let plain = encode(record, Codec::Zstd, Quality::Default).unwrap();
+        assert!(encoded.len() < plain.len());
+        assert_eq!(
+            decode_with_dictionary(&encoded, &dictionary).unwrap(),
+            *record
+        );
+    }
+
+    #[test]
+    fn dictionary_mismatch() {
+        let samples = samples();
+        let dictionary = Dictionary::train(&samples, 4096).unwrap();
+        let other = Dictionary::train(&samples[..500], 2048).unwrap();
+        assert_ne!(dictionary.id(), other.id());
+
+        let encoded = encode_with_dictionary(&samples[0], Quality::Default, &dictionary).unwrap();
+        assert!(matches!(
+            decode_with_dictionary(&encoded, &other),
+            Err(Error::DictionaryMismatch { expected, found })
+                if expected == dictionary.id() && found == other.id()
+        ));
+    }
+}
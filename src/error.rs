@@ -0,0 +1,183 @@
+use crate::Codec;
+use std::{error, fmt, io};
+
+/// A specialized `Result` type for smush operations.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The error type for encoding and decoding operations.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The codec's feature was not enabled when the crate was compiled.
+    CodecDisabled(Codec),
+
+    /// No built-in or registered codec has the given name.
+    UnknownCodec(String),
+
+    /// The string is not a valid quality level.
+    UnknownQuality(String),
+
+    /// The codec-specific options passed to `encode_with_options` do not
+    /// apply to the codec.
+    UnsupportedOptions(Codec),
+
+    /// A codec with the same name has already been registered.
+    AlreadyRegistered(&'static str),
+
+    /// The input could not be decoded by the codec.
+    Corrupt {
+        codec: Codec,
+        source: Box<dyn error::Error + Send + Sync>,
+    },
+
+    /// The input is not a valid smush frame.
+    InvalidFrame(String),
+
+    /// The checksum stored alongside the data did not match its contents.
+    ChecksumMismatch { expected: u32, found: u32 },
+
+    /// The zstd frame was encoded with a different dictionary than the one
+    /// supplied.
+    DictionaryMismatch { expected: u32, found: u32 },
+
+    /// The codec used to encode the input could not be detected.
+    Undetected,
+
+    /// Decoding stopped because the output grew beyond the configured limit.
+    LimitExceeded { limit: u64 },
+
+    /// An I/O error occurred while reading or writing data.
+    Io(io::Error),
+}
+
+impl Error {
+    pub(crate) fn corrupt<E>(codec: Codec, source: E) -> Self
+    where
+        E: Into<Box<dyn error::Error + Send + Sync>>,
+    {
+        Error::Corrupt {
+            codec,
+            source: source.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::CodecDisabled(codec) => {
+                write!(f, "encoding algorithm `{}` was not enabled", codec)
+            }
+            Error::UnknownCodec(name) => write!(f, "unknown encoding format: {}", name),
+            Error::UnknownQuality(name) => write!(f, "unknown quality level: {}", name),
+            Error::UnsupportedOptions(codec) => {
+                write!(f, "options do not apply to encoding algorithm `{}`", codec)
+            }
+            Error::AlreadyRegistered(name) => write!(f, "codec `{}` is already registered", name),
+            Error::Corrupt { codec, source } => {
+                write!(f, "failed to decode with {} - details: {}", codec, source)
+            }
+            Error::InvalidFrame(details) => write!(f, "invalid frame - details: {}", details),
+            Error::ChecksumMismatch { expected, found } => write!(
+                f,
+                "checksum mismatch - expected {:08x}, found {:08x}",
+                expected, found
+            ),
+            Error::DictionaryMismatch { expected, found } => write!(
+                f,
+                "dictionary mismatch - frame requires dictionary {}, found {}",
+                expected, found
+            ),
+            Error::Undetected => f.write_str("unable to detect the encoding algorithm"),
+            Error::LimitExceeded { limit } => {
+                write!(f, "decoded output exceeded the limit of {} bytes", limit)
+            }
+            Error::Io(err) => err.fmt(f),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Corrupt { source, .. } => Some(source.as_ref()),
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        // Unwrap errors that were converted to `io::Error` to pass through a
+        // `Read` or `Write` implementation.
+        if err.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            return *err.into_inner().unwrap().downcast::<Error>().unwrap();
+        }
+        Error::Io(err)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        let kind = match err {
+            Error::Io(err) => return err,
+            Error::CodecDisabled(_) => io::ErrorKind::Unsupported,
+            Error::UnknownCodec(_) | Error::UnknownQuality(_) | Error::UnsupportedOptions(_) => {
+                io::ErrorKind::InvalidInput
+            }
+            Error::AlreadyRegistered(_) => io::ErrorKind::AlreadyExists,
+            Error::Corrupt { .. }
+            | Error::InvalidFrame(_)
+            | Error::ChecksumMismatch { .. }
+            | Error::DictionaryMismatch { .. }
+            | Error::Undetected => io::ErrorKind::InvalidData,
+            Error::LimitExceeded { .. } => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn error_parse() {
+        assert!(matches!(
+            "nope".parse::<Codec>(),
+            Err(Error::UnknownCodec(name)) if name == "nope"
+        ));
+        assert!(matches!(
+            "level10".parse::<Quality>(),
+            Err(Error::UnknownQuality(name)) if name == "level10"
+        ));
+    }
+
+    #[cfg(feature = "zstd_support")]
+    #[test]
+    fn error_corrupt() {
+        use std::error::Error as _;
+
+        let err = decode(b"not zstd", Codec::Zstd).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Corrupt {
+                codec: Codec::Zstd,
+                ..
+            }
+        ));
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn error_io_round_trip() {
+        let err: std::io::Error = Error::UnknownCodec("nope".to_string()).into();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(matches!(Error::from(err), Error::UnknownCodec(_)));
+
+        let err = std::io::Error::new(std::io::ErrorKind::BrokenPipe, "broken");
+        let err: std::io::Error = Error::from(err).into();
+        assert_eq!(err.kind(), std::io::ErrorKind::BrokenPipe);
+    }
+}
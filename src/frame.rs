@@ -0,0 +1,321 @@
+//! A self-describing envelope around encoded data.
+//!
+//! A frame starts with a small header recording the codec and quality used,
+//! the length of the original data and a CRC-32 of the original data, so that
+//! it can be decoded without knowing how it was produced:
+//!
+//! | Size | Field                                  |
+//! |------|----------------------------------------|
+//! | 4    | Magic bytes `SMSH`                     |
+//! | 1    | Format version                         |
+//! | 1    | Codec id                               |
+//! | 1    | Quality id                             |
+//! | 8    | Original length (little-endian)        |
+//! | 4    | CRC-32 of original (little-endian)     |
+//! | 4    | Level, only for `Quality::Exact`       |
+//! | 1+n  | Codec name, only for custom codecs     |
+//!
+//! The encoded payload follows the header.
+
+use crate::{Codec, Error, Quality, Result};
+
+/// The magic bytes every frame starts with.
+pub const MAGIC: &[u8; 4] = b"SMSH";
+
+const VERSION: u8 = 1;
+const FIXED_HEADER_LEN: usize = 19;
+const CUSTOM_CODEC_ID: u8 = 0xff;
+const EXACT_QUALITY_ID: u8 = 0xff;
+
+/// The metadata stored at the start of a frame.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Header {
+    pub codec: Codec,
+    pub quality: Quality,
+    pub original_len: u64,
+    pub checksum: u32,
+}
+
+fn codec_to_id(codec: Codec) -> u8 {
+    match codec {
+        Codec::Identity => 0,
+        Codec::Gzip => 1,
+        Codec::Deflate => 2,
+        Codec::Zlib => 3,
+        Codec::Zstd => 4,
+        Codec::Brotli => 5,
+        Codec::Lz4 => 6,
+        Codec::Xz => 7,
+        Codec::BinCode => 8,
+        Codec::Base58 => 9,
+        Codec::Snappy => 10,
+        Codec::SnappyRaw => 11,
+        Codec::Bzip2 => 12,
+        Codec::Base64 => 13,
+        Codec::Base64Url => 14,
+        Codec::Base64NoPad => 15,
+        Codec::Base64UrlNoPad => 16,
+        Codec::Base32 => 17,
+        Codec::Base32Crockford => 18,
+        Codec::Hex => 19,
+        Codec::Base58Ripple => 20,
+        Codec::Base58Flickr => 21,
+        Codec::Base58Check => 22,
+        Codec::Base58CheckRipple => 23,
+        Codec::Base58CheckFlickr => 24,
+        Codec::Lz4Block => 25,
+        Codec::Lzma => 26,
+        Codec::Lzma2Raw => 27,
+        Codec::Custom(_) => CUSTOM_CODEC_ID,
+        Codec::__Nonexhaustive => unreachable!(),
+    }
+}
+
+fn id_to_codec(id: u8) -> Option<Codec> {
+    Some(match id {
+        0 => Codec::Identity,
+        1 => Codec::Gzip,
+        2 => Codec::Deflate,
+        3 => Codec::Zlib,
+        4 => Codec::Zstd,
+        5 => Codec::Brotli,
+        6 => Codec::Lz4,
+        7 => Codec::Xz,
+        8 => Codec::BinCode,
+        9 => Codec::Base58,
+        10 => Codec::Snappy,
+        11 => Codec::SnappyRaw,
+        12 => Codec::Bzip2,
+        13 => Codec::Base64,
+        14 => Codec::Base64Url,
+        15 => Codec::Base64NoPad,
+        16 => Codec::Base64UrlNoPad,
+        17 => Codec::Base32,
+        18 => Codec::Base32Crockford,
+        19 => Codec::Hex,
+        20 => Codec::Base58Ripple,
+        21 => Codec::Base58Flickr,
+        22 => Codec::Base58Check,
+        23 => Codec::Base58CheckRipple,
+        24 => Codec::Base58CheckFlickr,
+        25 => Codec::Lz4Block,
+        26 => Codec::Lzma,
+        27 => Codec::Lzma2Raw,
+        _ => return None,
+    })
+}
+
+fn quality_to_id(quality: Quality) -> u8 {
+    match quality {
+        Quality::Default => 0,
+        Quality::Level1 => 1,
+        Quality::Level2 => 2,
+        Quality::Level3 => 3,
+        Quality::Level4 => 4,
+        Quality::Level5 => 5,
+        Quality::Level6 => 6,
+        Quality::Level7 => 7,
+        Quality::Level8 => 8,
+        Quality::Level9 => 9,
+        Quality::Maximum => 10,
+        Quality::Exact(_) => EXACT_QUALITY_ID,
+    }
+}
+
+fn id_to_quality(id: u8) -> Option<Quality> {
+    Some(match id {
+        0 => Quality::Default,
+        1 => Quality::Level1,
+        2 => Quality::Level2,
+        3 => Quality::Level3,
+        4 => Quality::Level4,
+        5 => Quality::Level5,
+        6 => Quality::Level6,
+        7 => Quality::Level7,
+        8 => Quality::Level8,
+        9 => Quality::Level9,
+        10 => Quality::Maximum,
+        _ => return None,
+    })
+}
+
+/// Encodes `data` with `codec` and wraps the result in a frame.
+pub fn encode(data: &[u8], codec: Codec, quality: Quality) -> Result<Vec<u8>> {
+    let payload = crate::encode(data, codec, quality)?;
+
+    let mut buf = Vec::with_capacity(FIXED_HEADER_LEN + payload.len());
+    buf.extend_from_slice(MAGIC);
+    buf.push(VERSION);
+    buf.push(codec_to_id(codec));
+    buf.push(quality_to_id(quality));
+    buf.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    buf.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+    if let Quality::Exact(level) = quality {
+        buf.extend_from_slice(&level.to_le_bytes());
+    }
+    if let Codec::Custom(name) = codec {
+        if name.len() > u8::MAX as usize {
+            return Err(Error::InvalidFrame(format!(
+                "codec name is too long to frame: {}",
+                name
+            )));
+        }
+        buf.push(name.len() as u8);
+        buf.extend_from_slice(name.as_bytes());
+    }
+    buf.extend_from_slice(&payload);
+    Ok(buf)
+}
+
+/// Decodes a frame produced by `encode`, verifying its length and checksum.
+pub fn decode(data: &[u8]) -> Result<Vec<u8>> {
+    let (header, payload) = parse(data)?;
+    // The header's length bounds the output, so a small frame cannot decode
+    // to more than it claims.
+    let options = crate::DecodeOptions::new().max_output_bytes(header.original_len);
+    let buf = match crate::decode_with(payload, header.codec, &options) {
+        Err(Error::LimitExceeded { .. }) => {
+            return Err(Error::InvalidFrame(format!(
+                "length mismatch - expected {} bytes, decoded more",
+                header.original_len
+            )))
+        }
+        result => result?,
+    };
+    if buf.len() as u64 != header.original_len {
+        return Err(Error::InvalidFrame(format!(
+            "length mismatch - expected {} bytes, decoded {}",
+            header.original_len,
+            buf.len()
+        )));
+    }
+    let checksum = crc32fast::hash(&buf);
+    if checksum != header.checksum {
+        return Err(Error::ChecksumMismatch {
+            expected: header.checksum,
+            found: checksum,
+        });
+    }
+    Ok(buf)
+}
+
+/// Reads the header at the start of a frame without decoding the payload.
+pub fn header(data: &[u8]) -> Result<Header> {
+    parse(data).map(|(header, _)| header)
+}
+
+/// Returns true if `data` starts with the frame magic bytes.
+pub fn is_framed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn parse(data: &[u8]) -> Result<(Header, &[u8])> {
+    if data.len() < FIXED_HEADER_LEN || !is_framed(data) {
+        return Err(Error::InvalidFrame("data is not a smush frame".to_string()));
+    }
+    if data[4] != VERSION {
+        return Err(Error::InvalidFrame(format!(
+            "unsupported frame version: {}",
+            data[4]
+        )));
+    }
+
+    let mut rest = &data[FIXED_HEADER_LEN..];
+    let quality = match data[6] {
+        EXACT_QUALITY_ID => {
+            let level = rest
+                .get(..4)
+                .ok_or_else(|| Error::InvalidFrame("truncated frame header".to_string()))?;
+            let mut bytes = [0u8; 4];
+            bytes.copy_from_slice(level);
+            rest = &rest[4..];
+            Quality::Exact(i32::from_le_bytes(bytes))
+        }
+        id => id_to_quality(id)
+            .ok_or_else(|| Error::InvalidFrame(format!("unknown frame quality id: {}", id)))?,
+    };
+    let codec = match data[5] {
+        CUSTOM_CODEC_ID => {
+            let len = *rest
+                .first()
+                .ok_or_else(|| Error::InvalidFrame("truncated frame header".to_string()))?
+                as usize;
+            let name = rest
+                .get(1..1 + len)
+                .ok_or_else(|| Error::InvalidFrame("truncated frame header".to_string()))?;
+            let name = std::str::from_utf8(name).map_err(|err| {
+                Error::InvalidFrame(format!("invalid codec name in frame: {}", err))
+            })?;
+            rest = &rest[1 + len..];
+            name.parse()?
+        }
+        id => id_to_codec(id)
+            .ok_or_else(|| Error::InvalidFrame(format!("unknown frame codec id: {}", id)))?,
+    };
+
+    let mut original_len = [0u8; 8];
+    original_len.copy_from_slice(&data[7..15]);
+    let mut checksum = [0u8; 4];
+    checksum.copy_from_slice(&data[15..19]);
+
+    let header = Header {
+        codec,
+        quality,
+        original_len: u64::from_le_bytes(original_len),
+        checksum: u32::from_le_bytes(checksum),
+    };
+    Ok((header, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const TEST_DATA: &[u8] = include_bytes!("ipsum.txt");
+
+    #[test]
+    fn frame_round_trip() {
+        for &codec in ALL_CODECS.iter().filter(|&&codec| is_codec_enabled(codec)) {
+            let framed = encode_framed(TEST_DATA, codec, Quality::Level3).unwrap();
+            let header = frame::header(&framed).unwrap();
+            assert_eq!(header.codec, codec);
+            assert_eq!(header.quality, Quality::Level3);
+            assert_eq!(header.original_len, TEST_DATA.len() as u64);
+            assert_eq!(decode_framed(&framed).unwrap(), TEST_DATA, "{}", codec);
+        }
+    }
+
+    #[test]
+    fn frame_detects_corruption() {
+        let mut framed = encode_framed(TEST_DATA, Codec::Identity, Quality::Default).unwrap();
+        let last = framed.len() - 1;
+        framed[last] ^= 0xff;
+        assert!(decode_framed(&framed).is_err());
+        assert!(decode_framed(&framed[..10]).is_err());
+        assert!(decode_framed(TEST_DATA).is_err());
+    }
+
+    #[test]
+    fn frame_limits_output_to_original_len() {
+        let zeros = vec![0u8; 1 << 20];
+        for &codec in &[Codec::Identity, Codec::Zstd, Codec::Deflate] {
+            if !is_codec_enabled(codec) {
+                continue;
+            }
+            let mut framed = encode_framed(&zeros, codec, Quality::Default).unwrap();
+            framed[7..15].copy_from_slice(&16u64.to_le_bytes());
+            assert!(
+                matches!(decode_framed(&framed), Err(Error::InvalidFrame(_))),
+                "{}",
+                codec
+            );
+        }
+    }
+
+    #[test]
+    fn frame_exact_quality() {
+        let framed = encode_framed(TEST_DATA, Codec::Identity, Quality::Exact(-5)).unwrap();
+        assert_eq!(frame::header(&framed).unwrap().quality, Quality::Exact(-5));
+        assert_eq!(decode_framed(&framed).unwrap(), TEST_DATA);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{codecs, Quality, Result};
+use std::ops::Range;
+
+/// The metadata stored in the header of a gzip member.
+///
+/// The default header is anonymous, with a zero modification time and an
+/// unknown operating system, so that encoding the same data always produces
+/// the same output.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GzipHeader {
+    /// The name of the original file, without a trailing zero byte.
+    pub filename: Option<Vec<u8>>,
+
+    /// A comment, without a trailing zero byte.
+    pub comment: Option<Vec<u8>>,
+
+    /// The extra field, at most 65535 bytes long.
+    pub extra: Option<Vec<u8>>,
+
+    /// The modification time of the original file in seconds since the Unix
+    /// epoch, or zero if none is available.
+    pub mtime: u32,
+
+    /// The operating system the data was compressed on, where 255 means
+    /// unknown.
+    pub operating_system: u8,
+}
+
+impl GzipHeader {
+    pub fn new() -> Self {
+        GzipHeader {
+            filename: None,
+            comment: None,
+            extra: None,
+            mtime: 0,
+            operating_system: 255,
+        }
+    }
+
+    pub fn filename<T: Into<Vec<u8>>>(mut self, filename: T) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    pub fn comment<T: Into<Vec<u8>>>(mut self, comment: T) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn extra<T: Into<Vec<u8>>>(mut self, extra: T) -> Self {
+        self.extra = Some(extra.into());
+        self
+    }
+
+    pub fn mtime(mut self, mtime: u32) -> Self {
+        self.mtime = mtime;
+        self
+    }
+
+    pub fn operating_system(mut self, operating_system: u8) -> Self {
+        self.operating_system = operating_system;
+        self
+    }
+}
+
+impl Default for GzipHeader {
+    fn default() -> Self {
+        GzipHeader::new()
+    }
+}
+
+/// The position of one member of a multi-member gzip stream.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GzipMember {
+    pub header: GzipHeader,
+
+    /// The byte range of the member in the compressed input.
+    pub compressed: Range<usize>,
+
+    /// The byte range of the member's data in the decoded output.
+    pub decoded: Range<usize>,
+}
+
+/// Encodes `data` with gzip, writing `header` at the start of the member.
+///
+/// Fails with an `InvalidInput` I/O error if the filename or comment contain
+/// a zero byte, or the extra field is too long.
+pub fn encode_gzip_with_header(
+    data: &[u8],
+    quality: Quality,
+    header: &GzipHeader,
+) -> Result<Vec<u8>> {
+    codecs::gzip::encode_with_header(data, quality, header)
+}
+
+/// Decodes every member of gzip `data`, returning it along with the header of
+/// the first member.
+pub fn decode_gzip_with_header(data: &[u8]) -> Result<(Vec<u8>, GzipHeader)> {
+    codecs::gzip::decode_with_header(data)
+}
+
+/// Decodes every member of gzip `data`, like `decode`, also returning where
+/// each member starts and ends in the input and output.
+pub fn decode_gzip_members(data: &[u8]) -> Result<(Vec<u8>, Vec<GzipMember>)> {
+    codecs::gzip::decode_members(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::io::Read;
+
+    const TEST_DATA: &[u8] = include_bytes!("ipsum.txt");
+
+    #[test]
+    fn gzip_header_round_trip() {
+        let header = GzipHeader::new()
+            .filename("ipsum.txt")
+            .comment("lorem ipsum")
+            .extra(vec![b'S', b'M', 2, 0, 1, 2])
+            .mtime(1_577_836_800)
+            .operating_system(3);
+        let encoded = encode_gzip_with_header(TEST_DATA, Quality::Default, &header).unwrap();
+        assert_eq!(decode(&encoded, Codec::Gzip).unwrap(), TEST_DATA);
+
+        let (decoded, decoded_header) = decode_gzip_with_header(&encoded).unwrap();
+        assert_eq!(decoded, TEST_DATA);
+        assert_eq!(decoded_header, header);
+    }
+
+    #[test]
+    fn gzip_header_default() {
+        let encoded =
+            encode_gzip_with_header(TEST_DATA, Quality::Default, &GzipHeader::default()).unwrap();
+        assert_eq!(
+            encoded,
+            encode(TEST_DATA, Codec::Gzip, Quality::Default).unwrap()
+        );
+        assert_eq!(&encoded[4..8], &[0, 0, 0, 0]);
+        assert_eq!(
+            decode_gzip_with_header(&encoded).unwrap().1,
+            GzipHeader::default()
+        );
+    }
+
+    #[test]
+    fn gzip_members() {
+        let (first, second) = TEST_DATA.split_at(1000);
+        let mut encoded = encode(first, Codec::Gzip, Quality::Default).unwrap();
+        let first_len = encoded.len();
+        let header = GzipHeader::new().filename("second");
+        encoded.extend(encode_gzip_with_header(second, Quality::Level1, &header).unwrap());
+
+        assert_eq!(decode(&encoded, Codec::Gzip).unwrap(), TEST_DATA);
+        let mut decoded = Vec::new();
+        Decoder::new(&encoded[..], Codec::Gzip)
+            .unwrap()
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, TEST_DATA);
+
+        let (decoded, members) = decode_gzip_members(&encoded).unwrap();
+        assert_eq!(decoded, TEST_DATA);
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].compressed, 0..first_len);
+        assert_eq!(members[0].decoded, 0..1000);
+        assert_eq!(members[0].header, GzipHeader::default());
+        assert_eq!(members[1].compressed, first_len..encoded.len());
+        assert_eq!(members[1].decoded, 1000..TEST_DATA.len());
+        assert_eq!(members[1].header, header);
+
+        assert!(decode_gzip_members(&encoded[..encoded.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn gzip_header_invalid() {
+        let header = GzipHeader::new().filename("a\0b");
+        assert!(encode_gzip_with_header(TEST_DATA, Quality::Default, &header).is_err());
+        let header = GzipHeader::new().extra(vec![0; 70_000]);
+        assert!(encode_gzip_with_header(TEST_DATA, Quality::Default, &header).is_err());
+        assert!(decode_gzip_with_header(b"not gzip").is_err());
+    }
+}
@@ -1,10 +1,63 @@
-use std::{fmt, io, str};
+use std::{fmt, ops::RangeInclusive, str};
 
+#[cfg(feature = "async")]
+mod async_io;
 mod codecs;
+mod detect;
+#[cfg(feature = "zstd_support")]
+mod dictionary;
+mod error;
+pub mod frame;
+#[cfg(feature = "gzip_support")]
+mod gzip;
+mod limits;
+mod options;
+pub mod parallel;
+mod pipeline;
+mod registry;
+#[cfg(feature = "zstd_support")]
+mod seekable;
+mod stream;
+#[cfg(feature = "zstd_support")]
+mod zstd_frames;
+
+#[cfg(feature = "async")]
+pub use crate::async_io::{decode_async, encode_async, AsyncDecoder, AsyncEncoder};
+pub use crate::detect::{decode_auto, detect, detect_candidates, Detection};
+#[cfg(feature = "zstd_support")]
+pub use crate::dictionary::{decode_with_dictionary, encode_with_dictionary, Dictionary};
+pub use crate::error::{Error, Result};
+pub use crate::frame::{decode as decode_framed, encode as encode_framed};
+#[cfg(feature = "gzip_support")]
+pub use crate::gzip::{
+    decode_gzip_members, decode_gzip_with_header, encode_gzip_with_header, GzipHeader, GzipMember,
+};
+pub use crate::limits::{decode_with, DecodeOptions};
+pub use crate::options::{
+    decode_with_options, encode_with_options, BinCodeEndian, BinCodeIntEncoding, BinCodeOptions,
+    BrotliOptions, CodecOptions, FlateOptions, Lz4BlockSize, Lz4Mode, Lz4Options, XzBcj, XzCheck,
+    XzOptions, ZstdOptions,
+};
+pub use crate::parallel::{decode_parallel, encode_parallel};
+pub use crate::pipeline::Pipeline;
+pub use crate::registry::{register_codec, registry, Capabilities, CodecImpl, Registry};
+#[cfg(feature = "zstd_support")]
+pub use crate::seekable::{encode_seekable, SeekableDecoder};
+pub use crate::stream::{Decoder, Encoder};
+#[cfg(feature = "zstd_support")]
+pub use crate::zstd_frames::{
+    decode_zstd_frames, encode_zstd_skippable, zstd_frames, ZstdFrameInfo, ZstdFrameKind,
+    ZstdFrames, ZstdSkippableFrame,
+};
 
 use crate::{Codec::*, Quality::*};
 
+/// Every built-in codec, for tests that cover all of them.
+#[cfg(test)]
+pub(crate) const ALL_CODECS: &[Codec] = registry::BUILTIN_CODECS;
+
 /// A value to represent an encoding
+#[allow(clippy::manual_non_exhaustive)]
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Codec {
     /// The `gzip` encoding.
@@ -22,64 +75,211 @@ pub enum Codec {
     /// The `brotli` encoding.
     Brotli,
 
+    /// The `bzip2` encoding.
+    Bzip2,
+
     /// The 'lz4' encoding.
     Lz4,
 
+    /// The `lz4` block format, without framing, prefixed with the decoded
+    /// size.
+    Lz4Block,
+
+    /// The `snappy` encoding, using the framing format.
+    Snappy,
+
+    /// The `snappy` block encoding, without framing.
+    SnappyRaw,
+
     /// The 'xz' encoding (also known as `lzma`).
     Xz,
 
+    /// The legacy `lzma` encoding, also known as LZMA-alone, used by `.lzma`
+    /// files.
+    ///
+    /// `decode` rejects streams whose dictionary is larger than 64 MiB. Use
+    /// `decode_with_options` with `XzOptions::dict_size` to accept them.
+    Lzma,
+
+    /// A raw `lzma2` stream, without the `.xz` container.
+    ///
+    /// Raw streams do not record their dictionary size, so `decode` expects
+    /// data encoded with a dictionary of at most 64 MiB, which covers every
+    /// preset, and allocates no more than the decoded size of the stream.
+    /// Use `decode_with_options` with `XzOptions::dict_size` for larger
+    /// dictionaries.
+    Lzma2Raw,
+
     /// The 'bincode' encoding.
     BinCode,
 
-    /// The 'base58' encoding.
+    /// The 'base58' encoding, using the bitcoin alphabet.
     Base58,
 
+    /// The `base58` encoding, using the Ripple alphabet.
+    Base58Ripple,
+
+    /// The `base58` encoding, using the Flickr alphabet.
+    Base58Flickr,
+
+    /// The `base58check` encoding, which appends the first 4 bytes of the
+    /// double SHA-256 of the data. The version byte, if any, is expected to
+    /// be the first byte of the data.
+    Base58Check,
+
+    /// The `base58check` encoding, using the Ripple alphabet.
+    Base58CheckRipple,
+
+    /// The `base58check` encoding, using the Flickr alphabet.
+    Base58CheckFlickr,
+
+    /// The standard `base64` encoding, with padding.
+    Base64,
+
+    /// The URL and filename safe `base64` encoding, with padding.
+    Base64Url,
+
+    /// The standard `base64` encoding, without padding.
+    Base64NoPad,
+
+    /// The URL and filename safe `base64` encoding, without padding.
+    Base64UrlNoPad,
+
+    /// The RFC 4648 `base32` encoding, with padding.
+    Base32,
+
+    /// Crockford's `base32` encoding, without padding.
+    Base32Crockford,
+
+    /// The lowercase `hex` encoding.
+    Hex,
+
     /// The `identity` encoding.
     Identity,
 
+    /// A codec added with `register_codec`, identified by its name.
+    Custom(&'static str),
+
     #[doc(hidden)]
     // Silence "unreachable pattern" warnings when features are enabled.
     __Nonexhaustive,
 }
 
-impl fmt::Display for Codec {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(match self {
+impl Codec {
+    /// The name used by `Display` and `FromStr`.
+    pub fn name(&self) -> &'static str {
+        match self {
             Gzip => "gzip",
             Deflate => "deflate",
             Zlib => "zlib",
             Zstd => "zstd",
             Brotli => "brotli",
+            Bzip2 => "bzip2",
             Lz4 => "lz4",
+            Lz4Block => "lz4-block",
+            Snappy => "snappy",
+            SnappyRaw => "snappy-raw",
             Xz => "xz",
+            Lzma => "lzma",
+            Lzma2Raw => "lzma2-raw",
             BinCode => "bincode",
             Base58 => "base58",
+            Base58Ripple => "base58-ripple",
+            Base58Flickr => "base58-flickr",
+            Base58Check => "base58check",
+            Base58CheckRipple => "base58check-ripple",
+            Base58CheckFlickr => "base58check-flickr",
+            Base64 => "base64",
+            Base64Url => "base64url",
+            Base64NoPad => "base64-nopad",
+            Base64UrlNoPad => "base64url-nopad",
+            Base32 => "base32",
+            Base32Crockford => "base32-crockford",
+            Hex => "hex",
             Identity => "identity",
+            Custom(name) => name,
             __Nonexhaustive => unreachable!(),
-        })
+        }
+    }
+
+    /// The native levels accepted by `Quality::Exact`, or `None` if the codec
+    /// has no levels or is not enabled.
+    pub fn level_range(&self) -> Option<RangeInclusive<i32>> {
+        match self {
+            #[cfg(feature = "gzip_support")]
+            Gzip => Some(codecs::gzip::level_range()),
+
+            #[cfg(feature = "deflate_support")]
+            Deflate => Some(codecs::deflate::level_range()),
+
+            #[cfg(feature = "zlib_support")]
+            Zlib => Some(codecs::zlib::level_range()),
+
+            #[cfg(feature = "zstd_support")]
+            Zstd => Some(codecs::zstd::level_range()),
+
+            #[cfg(feature = "brotli_support")]
+            Brotli => Some(codecs::brotli::level_range()),
+
+            #[cfg(feature = "bzip2_support")]
+            Bzip2 => Some(codecs::bzip2::level_range()),
+
+            #[cfg(feature = "lz4_support")]
+            Lz4 | Lz4Block => Some(codecs::lz4::level_range()),
+
+            #[cfg(feature = "xz_support")]
+            Xz => Some(codecs::xz::level_range()),
+
+            #[cfg(feature = "xz_support")]
+            Lzma | Lzma2Raw => Some(codecs::lzma::level_range()),
+
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
     }
 }
 
 impl str::FromStr for Codec {
-    type Err = std::io::Error;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    type Err = Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         let encoding = match s {
             "deflate" => Deflate,
             "gzip" => Gzip,
             "zlib" => Zlib,
             "zstd" => Zstd,
             "brotli" => Brotli,
+            "bzip2" => Bzip2,
             "lz4" => Lz4,
+            "lz4-block" => Lz4Block,
+            "snappy" => Snappy,
+            "snappy-raw" => SnappyRaw,
             "xz" => Xz,
+            "lzma" => Lzma,
+            "lzma2-raw" => Lzma2Raw,
             "bincode" => BinCode,
             "base58" => Base58,
+            "base58-ripple" => Base58Ripple,
+            "base58-flickr" => Base58Flickr,
+            "base58check" => Base58Check,
+            "base58check-ripple" => Base58CheckRipple,
+            "base58check-flickr" => Base58CheckFlickr,
+            "base64" => Base64,
+            "base64url" => Base64Url,
+            "base64-nopad" => Base64NoPad,
+            "base64url-nopad" => Base64UrlNoPad,
+            "base32" => Base32,
+            "base32-crockford" => Base32Crockford,
+            "hex" => Hex,
             "identity" => Identity,
-            other => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("unknown encoding format: {}", other),
-                ))
-            }
+            other => match registry::lookup_custom(other) {
+                Some(codec) => Custom(codec.name()),
+                None => return Err(Error::UnknownCodec(other.to_string())),
+            },
         };
 
         Ok(encoding)
@@ -100,6 +300,9 @@ pub enum Quality {
     Level8,
     Level9,
     Maximum,
+
+    /// A native level of the codec, clamped to `Codec::level_range`.
+    Exact(i32),
 }
 
 impl fmt::Display for Quality {
@@ -116,13 +319,14 @@ impl fmt::Display for Quality {
             Level8 => "level8",
             Level9 => "level9",
             Maximum => "maximum",
+            Exact(level) => return write!(f, "{}", level),
         })
     }
 }
 
 impl str::FromStr for Quality {
-    type Err = std::io::Error;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    type Err = Error;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         let quality = match s {
             "default" => Default,
             "level1" => Level1,
@@ -135,19 +339,17 @@ impl str::FromStr for Quality {
             "level8" => Level8,
             "level9" => Level9,
             "maximum" => Maximum,
-            other => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    format!("unknown quality level: {}", other),
-                ))
-            }
+            other => match other.parse() {
+                Ok(level) => Exact(level),
+                Err(_) => return Err(Error::UnknownQuality(other.to_string())),
+            },
         };
 
         Ok(quality)
     }
 }
 
-pub fn encode(data: &[u8], codec: Codec, quality: Quality) -> io::Result<Vec<u8>> {
+pub fn encode(data: &[u8], codec: Codec, quality: Quality) -> Result<Vec<u8>> {
     match codec {
         Identity => Ok(data.to_vec()),
 
@@ -166,26 +368,56 @@ pub fn encode(data: &[u8], codec: Codec, quality: Quality) -> io::Result<Vec<u8>
         #[cfg(feature = "brotli_support")]
         Brotli => codecs::brotli::encode(data, quality),
 
+        #[cfg(feature = "bzip2_support")]
+        Bzip2 => codecs::bzip2::encode(data, quality),
+
         #[cfg(feature = "lz4_support")]
         Lz4 => codecs::lz4::encode(data, quality),
 
+        #[cfg(feature = "lz4_support")]
+        Lz4Block => codecs::lz4::encode_block(data, quality),
+
+        #[cfg(feature = "snappy_support")]
+        Snappy => codecs::snappy::encode(data, quality),
+
+        #[cfg(feature = "snappy_support")]
+        SnappyRaw => codecs::snappy::encode_raw(data, quality),
+
         #[cfg(feature = "xz_support")]
         Xz => codecs::xz::encode(data, quality),
 
+        #[cfg(feature = "xz_support")]
+        Lzma => codecs::lzma::encode(data, quality),
+
+        #[cfg(feature = "xz_support")]
+        Lzma2Raw => codecs::lzma::encode_raw2(data, quality),
+
         #[cfg(feature = "bincode_support")]
         BinCode => codecs::bincode::encode(data, quality),
 
         #[cfg(feature = "base58_support")]
-        Base58 => codecs::base58::encode(data, quality),
+        Base58 | Base58Ripple | Base58Flickr | Base58Check | Base58CheckRipple
+        | Base58CheckFlickr => codecs::base58::encode(data, codec),
+
+        #[cfg(feature = "base64_support")]
+        Base64 | Base64Url | Base64NoPad | Base64UrlNoPad => codecs::base64::encode(data, codec),
+
+        #[cfg(feature = "base32_support")]
+        Base32 | Base32Crockford => codecs::base32::encode(data, codec),
 
-        disabled => Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("encoding algorithm `{}` was not enabled", disabled),
-        )),
+        #[cfg(feature = "hex_support")]
+        Hex => codecs::hex::encode(data, codec),
+
+        Custom(name) => match registry::lookup_custom(name) {
+            Some(codec) => codec.encode(data, quality),
+            None => Err(Error::UnknownCodec(name.to_string())),
+        },
+
+        disabled => Err(Error::CodecDisabled(disabled)),
     }
 }
 
-pub fn decode(data: &[u8], codec: Codec) -> io::Result<Vec<u8>> {
+pub fn decode(data: &[u8], codec: Codec) -> Result<Vec<u8>> {
     match codec {
         Identity => Ok(data.to_vec()),
 
@@ -204,22 +436,52 @@ pub fn decode(data: &[u8], codec: Codec) -> io::Result<Vec<u8>> {
         #[cfg(feature = "brotli_support")]
         Brotli => codecs::brotli::decode(data),
 
+        #[cfg(feature = "bzip2_support")]
+        Bzip2 => codecs::bzip2::decode(data),
+
         #[cfg(feature = "lz4_support")]
         Lz4 => codecs::lz4::decode(data),
 
+        #[cfg(feature = "lz4_support")]
+        Lz4Block => codecs::lz4::decode_block(data),
+
+        #[cfg(feature = "snappy_support")]
+        Snappy => codecs::snappy::decode(data),
+
+        #[cfg(feature = "snappy_support")]
+        SnappyRaw => codecs::snappy::decode_raw(data),
+
         #[cfg(feature = "xz_support")]
         Xz => codecs::xz::decode(data),
 
+        #[cfg(feature = "xz_support")]
+        Lzma => codecs::lzma::decode(data),
+
+        #[cfg(feature = "xz_support")]
+        Lzma2Raw => codecs::lzma::decode_raw2(data),
+
         #[cfg(feature = "bincode_support")]
         BinCode => codecs::bincode::decode(data),
 
         #[cfg(feature = "base58_support")]
-        Base58 => codecs::base58::decode(data),
+        Base58 | Base58Ripple | Base58Flickr | Base58Check | Base58CheckRipple
+        | Base58CheckFlickr => codecs::base58::decode(data, codec),
+
+        #[cfg(feature = "base64_support")]
+        Base64 | Base64Url | Base64NoPad | Base64UrlNoPad => codecs::base64::decode(data, codec),
 
-        disabled => Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("encoding algorithm `{}` was not enabled", disabled),
-        )),
+        #[cfg(feature = "base32_support")]
+        Base32 | Base32Crockford => codecs::base32::decode(data, codec),
+
+        #[cfg(feature = "hex_support")]
+        Hex => codecs::hex::decode(data, codec),
+
+        Custom(name) => match registry::lookup_custom(name) {
+            Some(codec) => codec.decode(data),
+            None => Err(Error::UnknownCodec(name.to_string())),
+        },
+
+        disabled => Err(Error::CodecDisabled(disabled)),
     }
 }
 
@@ -230,16 +492,30 @@ pub fn is_codec_enabled(codec: Codec) -> bool {
         Codec::Zlib => cfg!(feature = "zlib_support"),
         Codec::Zstd => cfg!(feature = "zstd_support"),
         Codec::Brotli => cfg!(feature = "brotli_support"),
-        Codec::Lz4 => cfg!(feature = "lz4_support"),
-        Codec::Xz => cfg!(feature = "xz_support"),
+        Codec::Bzip2 => cfg!(feature = "bzip2_support"),
+        Codec::Lz4 | Codec::Lz4Block => cfg!(feature = "lz4_support"),
+        Codec::Snappy | Codec::SnappyRaw => cfg!(feature = "snappy_support"),
+        Codec::Xz | Codec::Lzma | Codec::Lzma2Raw => cfg!(feature = "xz_support"),
         Codec::BinCode => cfg!(feature = "bincode_support"),
-        Codec::Base58 => cfg!(feature = "base58_support"),
+        Codec::Base58
+        | Codec::Base58Ripple
+        | Codec::Base58Flickr
+        | Codec::Base58Check
+        | Codec::Base58CheckRipple
+        | Codec::Base58CheckFlickr => cfg!(feature = "base58_support"),
+        Codec::Base64 | Codec::Base64Url | Codec::Base64NoPad | Codec::Base64UrlNoPad => {
+            cfg!(feature = "base64_support")
+        }
+        Codec::Base32 | Codec::Base32Crockford => cfg!(feature = "base32_support"),
+        Codec::Hex => cfg!(feature = "hex_support"),
         Codec::Identity => true,
+        Codec::Custom(name) => registry::lookup_custom(name).is_some(),
         _disabled => false,
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_borrow)]
 mod tests {
     use crate::*;
 
@@ -281,18 +557,49 @@ mod tests {
         encode(&TEST_DATA, Codec::Brotli, Quality::Default).unwrap();
     }
 
+    #[cfg(feature = "bzip2_support")]
+    #[test]
+    fn encode_bzip2() {
+        encode(&TEST_DATA, Codec::Bzip2, Quality::Default).unwrap();
+    }
+
     #[cfg(feature = "lz4_support")]
     #[test]
     fn encode_lz4() {
         encode(&TEST_DATA, Codec::Lz4, Quality::Default).unwrap();
     }
 
+    #[cfg(feature = "lz4_support")]
+    #[test]
+    fn encode_lz4_block() {
+        encode(&TEST_DATA, Codec::Lz4Block, Quality::Default).unwrap();
+    }
+
+    #[cfg(feature = "snappy_support")]
+    #[test]
+    fn encode_snappy() {
+        encode(&TEST_DATA, Codec::Snappy, Quality::Default).unwrap();
+        encode(&TEST_DATA, Codec::SnappyRaw, Quality::Default).unwrap();
+    }
+
     #[cfg(feature = "xz_support")]
     #[test]
     fn encode_xz() {
         encode(&TEST_DATA, Codec::Xz, Quality::Default).unwrap();
     }
 
+    #[cfg(feature = "xz_support")]
+    #[test]
+    fn encode_lzma() {
+        encode(&TEST_DATA, Codec::Lzma, Quality::Default).unwrap();
+    }
+
+    #[cfg(feature = "xz_support")]
+    #[test]
+    fn encode_lzma2_raw() {
+        encode(&TEST_DATA, Codec::Lzma2Raw, Quality::Default).unwrap();
+    }
+
     #[cfg(feature = "bincode_support")]
     #[test]
     fn encode_bincode() {
@@ -353,6 +660,23 @@ mod tests {
         assert_eq!(decoded, TEST_DATA);
     }
 
+    #[cfg(feature = "bzip2_support")]
+    #[test]
+    fn decode_bzip2() {
+        let encoded = encode(&TEST_DATA, Codec::Bzip2, Quality::Default).unwrap();
+        let decoded = decode(&encoded, Codec::Bzip2).unwrap();
+        assert_eq!(decoded, TEST_DATA);
+    }
+
+    #[cfg(feature = "bzip2_support")]
+    #[test]
+    fn decode_bzip2_multi_stream() {
+        let mut encoded = encode(&TEST_DATA, Codec::Bzip2, Quality::Level1).unwrap();
+        encoded.extend(encode(&TEST_DATA, Codec::Bzip2, Quality::Level9).unwrap());
+        let decoded = decode(&encoded, Codec::Bzip2).unwrap();
+        assert_eq!(decoded, [TEST_DATA, TEST_DATA].concat());
+    }
+
     #[cfg(feature = "lz4_support")]
     #[test]
     fn decode_lz4() {
@@ -361,6 +685,33 @@ mod tests {
         assert_eq!(decoded, TEST_DATA);
     }
 
+    #[cfg(feature = "lz4_support")]
+    #[test]
+    fn decode_lz4_block() {
+        let encoded = encode(&TEST_DATA, Codec::Lz4Block, Quality::Default).unwrap();
+        let decoded = decode(&encoded, Codec::Lz4Block).unwrap();
+        assert_eq!(decoded, TEST_DATA);
+        assert_eq!(
+            Codec::Lz4Block.to_string().parse::<Codec>().unwrap(),
+            Codec::Lz4Block
+        );
+
+        // A bare size prefix must not make lz4 allocate the claimed size.
+        assert!(decode(&[0xff, 0xff, 0xff, 0x7f], Codec::Lz4Block).is_err());
+        assert!(decode(&[0x00, 0x00, 0x00, 0x01, 0x00], Codec::Lz4Block).is_err());
+    }
+
+    #[cfg(feature = "snappy_support")]
+    #[test]
+    fn decode_snappy() {
+        for &codec in &[Codec::Snappy, Codec::SnappyRaw] {
+            let encoded = encode(&TEST_DATA, codec, Quality::Default).unwrap();
+            let decoded = decode(&encoded, codec).unwrap();
+            assert_eq!(decoded, TEST_DATA);
+            assert_eq!(codec.to_string().parse::<Codec>().unwrap(), codec);
+        }
+    }
+
     #[cfg(feature = "xz_support")]
     #[test]
     fn decode_xz() {
@@ -369,6 +720,30 @@ mod tests {
         assert_eq!(decoded, TEST_DATA);
     }
 
+    #[cfg(feature = "xz_support")]
+    #[test]
+    fn decode_lzma() {
+        for &codec in &[Codec::Lzma, Codec::Lzma2Raw] {
+            for &quality in &[Quality::Level1, Quality::Maximum] {
+                let encoded = encode(&TEST_DATA, codec, quality).unwrap();
+                let decoded = decode(&encoded, codec).unwrap();
+                assert_eq!(decoded, TEST_DATA);
+            }
+            assert_eq!(codec.to_string().parse::<Codec>().unwrap(), codec);
+            assert!(decode(b"not lzma data", codec).is_err());
+        }
+
+        // The LZMA-alone header: properties, dictionary size and unknown length.
+        let encoded = encode(&TEST_DATA, Codec::Lzma, Quality::Default).unwrap();
+        assert_eq!(&encoded[..5], &[0x5d, 0x00, 0x00, 0x80, 0x00]);
+        assert_eq!(&encoded[5..13], &[0xff; 8]);
+
+        let mut encoded = encode(&TEST_DATA, Codec::Lzma2Raw, Quality::Default).unwrap();
+        assert!(decode(&encoded[..encoded.len() - 1], Codec::Lzma2Raw).is_err());
+        encoded.push(0);
+        assert!(decode(&encoded, Codec::Lzma2Raw).is_err());
+    }
+
     #[cfg(feature = "bincode_support")]
     #[test]
     fn decode_bincode() {
@@ -384,4 +759,139 @@ mod tests {
         let decoded = decode(&encoded, Codec::Base58).unwrap();
         assert_eq!(decoded, TEST_DATA);
     }
+
+    #[cfg(feature = "base58_support")]
+    #[test]
+    fn decode_base58check() {
+        // Version byte 0 followed by the hash160 of a public key.
+        let address: &[u8] = &[
+            0x00, 0xf5, 0x4a, 0x58, 0x51, 0xe9, 0x37, 0x2b, 0x87, 0x81, 0x0a, 0x8e, 0x60, 0xcd,
+            0xd2, 0xe7, 0xcf, 0xd8, 0x0b, 0x6e, 0x31,
+        ];
+        let vectors: &[(Codec, &[u8])] = &[
+            (Codec::Base58Check, b"1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAs"),
+            (
+                Codec::Base58CheckRipple,
+                b"rPMyc2c8J2SqAAJqj2AXBNi8L1ZfRkX7w1",
+            ),
+            (
+                Codec::Base58CheckFlickr,
+                b"1omYBzBMizrQWWiQJzWwbeRMkSy7qKwtaS",
+            ),
+        ];
+        for &(codec, encoded) in vectors {
+            assert_eq!(encode(address, codec, Quality::Default).unwrap(), encoded);
+            assert_eq!(decode(encoded, codec).unwrap(), address);
+        }
+
+        assert!(matches!(
+            decode(b"1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAt", Codec::Base58Check),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+        // The same characters read with another alphabet decode to bytes
+        // whose checksum does not match.
+        assert!(matches!(
+            decode(
+                b"1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAs",
+                Codec::Base58CheckRipple
+            ),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+
+        // A valid payload with a flipped checksum byte.
+        let mut tampered = decode(b"1PMycacnJaSqwwJqjawXBErnLsZ7RkXUAs", Codec::Base58).unwrap();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 0x01;
+        let tampered = encode(&tampered, Codec::Base58, Quality::Default).unwrap();
+        assert!(matches!(
+            decode(&tampered, Codec::Base58Check),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn quality_exact() {
+        assert_eq!("19".parse::<Quality>().unwrap(), Quality::Exact(19));
+        assert_eq!("-5".parse::<Quality>().unwrap(), Quality::Exact(-5));
+        assert_eq!(Quality::Exact(-5).to_string(), "-5");
+        assert_eq!(Codec::Identity.level_range(), None);
+    }
+
+    #[cfg(feature = "zstd_support")]
+    #[test]
+    fn encode_zstd_exact() {
+        let range = Codec::Zstd.level_range().unwrap();
+        assert!(*range.start() < 0 && *range.end() >= 22);
+        for &level in &[-5, 1, 19] {
+            let encoded = encode(&TEST_DATA, Codec::Zstd, Quality::Exact(level)).unwrap();
+            assert_eq!(decode(&encoded, Codec::Zstd).unwrap(), TEST_DATA);
+        }
+    }
+
+    #[cfg(feature = "brotli_support")]
+    #[test]
+    fn encode_brotli_exact_clamped() {
+        assert_eq!(Codec::Brotli.level_range(), Some(0..=11));
+        assert_eq!(
+            encode(&TEST_DATA, Codec::Brotli, Quality::Exact(100)).unwrap(),
+            encode(&TEST_DATA, Codec::Brotli, Quality::Exact(11)).unwrap()
+        );
+    }
+
+    #[cfg(feature = "base64_support")]
+    #[test]
+    fn decode_base64() {
+        let vectors: &[(Codec, &[u8])] = &[
+            (Codec::Base64, b"+/8="),
+            (Codec::Base64Url, b"-_8="),
+            (Codec::Base64NoPad, b"+/8"),
+            (Codec::Base64UrlNoPad, b"-_8"),
+        ];
+        for &(codec, expected) in vectors {
+            assert_eq!(
+                encode(&[0xfb, 0xff], codec, Quality::Default).unwrap(),
+                expected
+            );
+            let encoded = encode(&TEST_DATA, codec, Quality::Default).unwrap();
+            assert_eq!(decode(&encoded, codec).unwrap(), TEST_DATA);
+            assert_eq!(codec.to_string().parse::<Codec>().unwrap(), codec);
+        }
+        assert_eq!(decode(b" aGVs\nbG8=\n", Codec::Base64).unwrap(), b"hello");
+        assert!(decode(b"aGVsbG8", Codec::Base64).is_err());
+    }
+
+    #[cfg(feature = "base32_support")]
+    #[test]
+    fn decode_base32() {
+        let vectors: &[(Codec, &[u8])] = &[
+            (Codec::Base32, b"MZXW6YTBOI======"),
+            (Codec::Base32Crockford, b"CSQPYRK1E8"),
+        ];
+        for &(codec, expected) in vectors {
+            assert_eq!(
+                encode(b"foobar", codec, Quality::Default).unwrap(),
+                expected
+            );
+            let encoded = encode(&TEST_DATA, codec, Quality::Default).unwrap();
+            assert_eq!(decode(&encoded, codec).unwrap(), TEST_DATA);
+            assert_eq!(codec.to_string().parse::<Codec>().unwrap(), codec);
+        }
+        assert_eq!(
+            decode(b"csqp-yrkl-e8\n", Codec::Base32Crockford).unwrap(),
+            b"foobar"
+        );
+    }
+
+    #[cfg(feature = "hex_support")]
+    #[test]
+    fn decode_hex() {
+        assert_eq!(
+            encode(b"foobar", Codec::Hex, Quality::Default).unwrap(),
+            b"666f6f626172"
+        );
+        let encoded = encode(&TEST_DATA, Codec::Hex, Quality::Default).unwrap();
+        assert_eq!(decode(&encoded, Codec::Hex).unwrap(), TEST_DATA);
+        assert_eq!(decode(b"66 6F 6F\n626172", Codec::Hex).unwrap(), b"foobar");
+        assert!(decode(b"666", Codec::Hex).is_err());
+    }
 }
@@ -0,0 +1,167 @@
+use crate::{Codec, Error, Result};
+use std::io::Read;
+
+/// Limits applied by `decode_with` to protect against decompression bombs.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct DecodeOptions {
+    /// The maximum number of bytes decoding may produce.
+    pub max_output_bytes: Option<u64>,
+
+    /// The maximum size of the decoded output relative to the encoded input.
+    pub max_ratio: Option<f64>,
+}
+
+impl DecodeOptions {
+    /// Creates options without any limits.
+    pub fn new() -> Self {
+        DecodeOptions::default()
+    }
+
+    pub fn max_output_bytes(mut self, max_output_bytes: u64) -> Self {
+        self.max_output_bytes = Some(max_output_bytes);
+        self
+    }
+
+    pub fn max_ratio(mut self, max_ratio: f64) -> Self {
+        self.max_ratio = Some(max_ratio);
+        self
+    }
+
+    /// The number of bytes decoding `input_len` bytes may produce.
+    fn limit(&self, input_len: usize) -> Option<u64> {
+        let ratio_limit = self
+            .max_ratio
+            .map(|ratio| (ratio.max(0.0) * input_len as f64).ceil() as u64);
+        match (self.max_output_bytes, ratio_limit) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Decodes `data` like `decode`, failing with `Error::LimitExceeded` as soon
+/// as the output grows beyond the limits in `options`.
+///
+/// Streaming codecs stop decoding once the limit is crossed. The length
+/// prefixes written by `BinCode`, `Lz4Block` and `SnappyRaw`, and the chunk
+/// headers of `Lzma2Raw`, are checked before anything is allocated. The
+/// output of text encodings is always smaller than their input, and custom
+/// codecs are checked once they have finished decoding.
+pub fn decode_with(data: &[u8], codec: Codec, options: &DecodeOptions) -> Result<Vec<u8>> {
+    let limit = match options.limit(data.len()) {
+        Some(limit) => limit,
+        None => return crate::decode(data, codec),
+    };
+
+    let buf = match codec {
+        #[cfg(feature = "bincode_support")]
+        Codec::BinCode => match crate::codecs::bincode::decoded_len(data) {
+            Some(len) if len > limit => return Err(Error::LimitExceeded { limit }),
+            _ => crate::decode(data, codec)?,
+        },
+
+        #[cfg(feature = "lz4_support")]
+        Codec::Lz4Block => match crate::codecs::lz4::decoded_len_block(data) {
+            Some(len) if len > limit => return Err(Error::LimitExceeded { limit }),
+            _ => crate::decode(data, codec)?,
+        },
+
+        #[cfg(feature = "xz_support")]
+        Codec::Lzma2Raw => match crate::codecs::lzma::decoded_len_raw2(data) {
+            Some(len) if len > limit => return Err(Error::LimitExceeded { limit }),
+            _ => crate::decode(data, codec)?,
+        },
+
+        #[cfg(feature = "snappy_support")]
+        Codec::SnappyRaw => match crate::codecs::snappy::decoded_len_raw(data) {
+            Some(len) if len > limit => return Err(Error::LimitExceeded { limit }),
+            _ => crate::decode(data, codec)?,
+        },
+
+        Codec::Identity
+        | Codec::Base58
+        | Codec::Base58Ripple
+        | Codec::Base58Flickr
+        | Codec::Base58Check
+        | Codec::Base58CheckRipple
+        | Codec::Base58CheckFlickr
+        | Codec::Base64
+        | Codec::Base64Url
+        | Codec::Base64NoPad
+        | Codec::Base64UrlNoPad
+        | Codec::Base32
+        | Codec::Base32Crockford
+        | Codec::Hex
+        | Codec::Custom(_) => crate::decode(data, codec)?,
+
+        _ => {
+            let mut buf = Vec::new();
+            crate::Decoder::new(data, codec)?
+                .take(limit + 1)
+                .read_to_end(&mut buf)
+                .map_err(|err| Error::corrupt(codec, err))?;
+            buf
+        }
+    };
+
+    if buf.len() as u64 > limit {
+        return Err(Error::LimitExceeded { limit });
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const TEST_DATA: &[u8] = include_bytes!("ipsum.txt");
+
+    #[test]
+    fn decode_with_limits() {
+        let len = TEST_DATA.len() as u64;
+        for &codec in ALL_CODECS.iter().filter(|&&codec| is_codec_enabled(codec)) {
+            let encoded = encode(TEST_DATA, codec, Quality::Default).unwrap();
+
+            let options = DecodeOptions::new().max_output_bytes(len);
+            assert_eq!(decode_with(&encoded, codec, &options).unwrap(), TEST_DATA);
+
+            let options = DecodeOptions::new().max_output_bytes(len - 1);
+            assert!(matches!(
+                decode_with(&encoded, codec, &options),
+                Err(Error::LimitExceeded { limit }) if limit == len - 1
+            ));
+        }
+    }
+
+    #[cfg(feature = "zstd_support")]
+    #[test]
+    fn decode_with_ratio() {
+        let zeros = vec![0u8; 1 << 20];
+        let encoded = encode(&zeros, Codec::Zstd, Quality::Default).unwrap();
+        let options = DecodeOptions::new().max_ratio(100.0);
+        assert!(matches!(
+            decode_with(&encoded, Codec::Zstd, &options),
+            Err(Error::LimitExceeded { .. })
+        ));
+        let options = DecodeOptions::new().max_ratio(1e6);
+        assert_eq!(decode_with(&encoded, Codec::Zstd, &options).unwrap(), zeros);
+    }
+
+    #[cfg(feature = "xz_support")]
+    #[test]
+    fn decode_with_lzma2_raw() {
+        use std::io::Read;
+        let zeros = vec![0u8; 8 << 20];
+        let encoded = encode(&zeros, Codec::Lzma2Raw, Quality::Level1).unwrap();
+        let options = DecodeOptions::new().max_output_bytes(1024);
+        assert!(matches!(
+            decode_with(&encoded, Codec::Lzma2Raw, &options),
+            Err(Error::LimitExceeded { limit: 1024 })
+        ));
+
+        let mut decoder = Decoder::new(&encoded[..], Codec::Lzma2Raw).unwrap();
+        let mut buf = [1u8; 1024];
+        decoder.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0u8; 1024]);
+    }
+}
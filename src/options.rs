@@ -0,0 +1,920 @@
+use crate::{Codec, Error, Quality, Result};
+
+/// Advanced zstd settings.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ZstdOptions {
+    pub quality: Quality,
+
+    /// Enables long distance matching, which finds repetitions far apart in
+    /// large inputs.
+    pub long_distance_matching: bool,
+
+    /// The log2 of the window size, from 10 to 31. Windows larger than 2^27
+    /// bytes require decoders configured to accept them, such as
+    /// `decode_with_options` given the same settings.
+    pub window_log: Option<u32>,
+
+    /// Appends a checksum of the original data to each frame.
+    pub checksum: bool,
+}
+
+impl ZstdOptions {
+    pub fn new(quality: Quality) -> Self {
+        ZstdOptions {
+            quality,
+            long_distance_matching: false,
+            window_log: None,
+            checksum: false,
+        }
+    }
+
+    pub fn long_distance_matching(mut self, long_distance_matching: bool) -> Self {
+        self.long_distance_matching = long_distance_matching;
+        self
+    }
+
+    pub fn window_log(mut self, window_log: u32) -> Self {
+        self.window_log = Some(window_log);
+        self
+    }
+
+    pub fn checksum(mut self, checksum: bool) -> Self {
+        self.checksum = checksum;
+        self
+    }
+}
+
+/// Advanced brotli settings.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct BrotliOptions {
+    pub quality: Quality,
+
+    /// The log2 of the sliding window size, from 10 to 24.
+    pub window: u32,
+
+    /// The log2 of the maximum input block size, from 16 to 24, or `None`
+    /// to let the encoder choose based on the quality.
+    pub block: Option<u32>,
+
+    /// Tunes the encoder for UTF-8 text rather than generic data.
+    pub text: bool,
+}
+
+impl BrotliOptions {
+    pub fn new(quality: Quality) -> Self {
+        BrotliOptions {
+            quality,
+            window: 20,
+            block: None,
+            text: false,
+        }
+    }
+
+    pub fn window(mut self, window: u32) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn block(mut self, block: u32) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn text(mut self, text: bool) -> Self {
+        self.text = text;
+        self
+    }
+}
+
+/// Advanced xz settings, also used by `Lzma` and `Lzma2Raw`.
+///
+/// `Lzma` streams have no room for an integrity check or filters, so they
+/// ignore `check` and reject `bcj` and `delta`. `Lzma2Raw` streams have no
+/// integrity check either, and must be decoded with the filters they were
+/// encoded with.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct XzOptions {
+    pub quality: Quality,
+
+    /// The LZMA2 dictionary size in bytes, overriding the preset's size.
+    /// When decoding `Lzma` and `Lzma2Raw`, the largest dictionary to accept
+    /// instead of `64 MiB`.
+    pub dict_size: Option<u32>,
+
+    /// Uses the slower "extreme" variant of the preset.
+    pub extreme: bool,
+
+    /// The integrity check stored with each block.
+    pub check: XzCheck,
+
+    /// A branch/call/jump filter that makes executable code for the given
+    /// architecture more compressible.
+    pub bcj: Option<XzBcj>,
+
+    /// The distance in bytes, from 1 to 256, of a delta filter applied
+    /// before compression. Suits fixed-width samples such as uncompressed
+    /// images or audio, e.g. 4 for RGBA pixels.
+    pub delta: Option<u32>,
+
+    /// The number of literal context bits, from 0 to 4. Together with
+    /// `literal_position_bits` it must not exceed 4.
+    pub literal_context_bits: Option<u32>,
+
+    /// The number of literal position bits, from 0 to 4.
+    pub literal_position_bits: Option<u32>,
+
+    /// The number of position bits, from 0 to 4.
+    pub position_bits: Option<u32>,
+
+    /// The match length, from 2 to 273, that the encoder accepts as good
+    /// enough without searching further.
+    pub nice_len: Option<u32>,
+}
+
+impl XzOptions {
+    pub fn new(quality: Quality) -> Self {
+        XzOptions {
+            quality,
+            dict_size: None,
+            extreme: false,
+            check: XzCheck::Crc64,
+            bcj: None,
+            delta: None,
+            literal_context_bits: None,
+            literal_position_bits: None,
+            position_bits: None,
+            nice_len: None,
+        }
+    }
+
+    pub fn dict_size(mut self, dict_size: u32) -> Self {
+        self.dict_size = Some(dict_size);
+        self
+    }
+
+    pub fn extreme(mut self, extreme: bool) -> Self {
+        self.extreme = extreme;
+        self
+    }
+
+    pub fn check(mut self, check: XzCheck) -> Self {
+        self.check = check;
+        self
+    }
+
+    pub fn bcj(mut self, bcj: XzBcj) -> Self {
+        self.bcj = Some(bcj);
+        self
+    }
+
+    pub fn delta(mut self, distance: u32) -> Self {
+        self.delta = Some(distance);
+        self
+    }
+
+    pub fn literal_context_bits(mut self, bits: u32) -> Self {
+        self.literal_context_bits = Some(bits);
+        self
+    }
+
+    pub fn literal_position_bits(mut self, bits: u32) -> Self {
+        self.literal_position_bits = Some(bits);
+        self
+    }
+
+    pub fn position_bits(mut self, bits: u32) -> Self {
+        self.position_bits = Some(bits);
+        self
+    }
+
+    pub fn nice_len(mut self, nice_len: u32) -> Self {
+        self.nice_len = Some(nice_len);
+        self
+    }
+}
+
+/// The integrity check of an xz stream.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum XzCheck {
+    None,
+    Crc32,
+    Crc64,
+    Sha256,
+}
+
+/// The architectures supported by the xz branch/call/jump filters.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum XzBcj {
+    /// 32-bit and 64-bit x86.
+    X86,
+    /// Big endian PowerPC.
+    PowerPc,
+    /// Itanium.
+    Ia64,
+    /// 32-bit ARM.
+    Arm,
+    /// ARM Thumb and Thumb-2.
+    ArmThumb,
+    Sparc,
+}
+
+/// The maximum size of the blocks in an lz4 frame.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Lz4BlockSize {
+    Default,
+    Max64KB,
+    Max256KB,
+    Max1MB,
+    Max4MB,
+}
+
+/// Selects between the fast and high compression (HC) lz4 encoders.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Lz4Mode {
+    /// Uses the fast encoder for levels below 3 and HC from level 3.
+    Auto,
+
+    /// Always uses the fast encoder, ignoring the quality.
+    Fast,
+
+    /// Always uses the HC encoder, raising levels below 3 to 3.
+    HighCompression,
+}
+
+/// Advanced lz4 settings, for both the `Lz4` frame format and `Lz4Block`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Lz4Options {
+    pub quality: Quality,
+    pub mode: Lz4Mode,
+    pub block_size: Lz4BlockSize,
+
+    /// Compresses each block on its own instead of referencing data in the
+    /// previous block, which reduces the ratio but allows random access.
+    pub independent_blocks: bool,
+
+    /// Appends a checksum of the original data to the frame.
+    pub content_checksum: bool,
+
+    /// Appends a checksum of the compressed data to every block of the frame.
+    /// On by default, as in `encode`.
+    pub block_checksum: bool,
+
+    /// Writes the decoded size as a 4-byte little-endian prefix before an
+    /// `Lz4Block`, as done by `compress_prepend_size` in other lz4 libraries.
+    pub prepend_size: bool,
+
+    /// The decoded size, or an upper bound of it, needed to decode an
+    /// `Lz4Block` written without a size prefix.
+    pub uncompressed_size: Option<u32>,
+}
+
+impl Lz4Options {
+    pub fn new(quality: Quality) -> Self {
+        Lz4Options {
+            quality,
+            mode: Lz4Mode::Auto,
+            block_size: Lz4BlockSize::Default,
+            independent_blocks: false,
+            content_checksum: true,
+            block_checksum: true,
+            prepend_size: true,
+            uncompressed_size: None,
+        }
+    }
+
+    pub fn mode(mut self, mode: Lz4Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn block_size(mut self, block_size: Lz4BlockSize) -> Self {
+        self.block_size = block_size;
+        self
+    }
+
+    pub fn independent_blocks(mut self, independent_blocks: bool) -> Self {
+        self.independent_blocks = independent_blocks;
+        self
+    }
+
+    pub fn content_checksum(mut self, content_checksum: bool) -> Self {
+        self.content_checksum = content_checksum;
+        self
+    }
+
+    pub fn block_checksum(mut self, block_checksum: bool) -> Self {
+        self.block_checksum = block_checksum;
+        self
+    }
+
+    pub fn prepend_size(mut self, prepend_size: bool) -> Self {
+        self.prepend_size = prepend_size;
+        self
+    }
+
+    pub fn uncompressed_size(mut self, uncompressed_size: u32) -> Self {
+        self.uncompressed_size = Some(uncompressed_size);
+        self
+    }
+}
+
+/// Settings shared by the `Gzip`, `Deflate` and `Zlib` codecs.
+///
+/// The pure Rust flate2 backend only supports choosing the compression
+/// level, so this currently carries just the quality.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FlateOptions {
+    pub quality: Quality,
+}
+
+impl FlateOptions {
+    pub fn new(quality: Quality) -> Self {
+        FlateOptions { quality }
+    }
+}
+
+/// How bincode writes the length prefix.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BinCodeIntEncoding {
+    /// An 8-byte integer, as written by `bincode::serialize`.
+    Fixed,
+
+    /// A variable length integer of 1 to 9 bytes.
+    Varint,
+}
+
+/// The byte order bincode writes integers in.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BinCodeEndian {
+    Little,
+    Big,
+}
+
+/// Advanced bincode settings, used with both `encode_with_options` and
+/// `decode_with_options` so that the output matches other bincode
+/// configurations.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct BinCodeOptions {
+    /// Unused, as bincode does not compress.
+    pub quality: Quality,
+    pub int_encoding: BinCodeIntEncoding,
+    pub endian: BinCodeEndian,
+
+    /// The maximum number of bytes read while decoding, after which decoding
+    /// fails with `Error::LimitExceeded`.
+    pub limit: Option<u64>,
+}
+
+impl BinCodeOptions {
+    pub fn new(quality: Quality) -> Self {
+        BinCodeOptions {
+            quality,
+            int_encoding: BinCodeIntEncoding::Fixed,
+            endian: BinCodeEndian::Little,
+            limit: None,
+        }
+    }
+
+    pub fn int_encoding(mut self, int_encoding: BinCodeIntEncoding) -> Self {
+        self.int_encoding = int_encoding;
+        self
+    }
+
+    pub fn endian(mut self, endian: BinCodeEndian) -> Self {
+        self.endian = endian;
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+macro_rules! impl_options {
+    ($($variant:ident => $options:ident),*) => {$(
+        impl Default for $options {
+            fn default() -> Self {
+                $options::new(Quality::Default)
+            }
+        }
+
+        impl From<Quality> for $options {
+            fn from(quality: Quality) -> Self {
+                $options::new(quality)
+            }
+        }
+
+        impl From<$options> for CodecOptions {
+            fn from(options: $options) -> Self {
+                CodecOptions::$variant(options)
+            }
+        }
+    )*};
+}
+
+/// Settings for `encode_with_options`, either a plain `Quality` that works
+/// with every codec or the advanced settings of a specific codec.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CodecOptions {
+    Quality(Quality),
+    Zstd(ZstdOptions),
+    Brotli(BrotliOptions),
+    Xz(XzOptions),
+    Lz4(Lz4Options),
+    Flate(FlateOptions),
+    BinCode(BinCodeOptions),
+}
+
+impl_options!(
+    Zstd => ZstdOptions,
+    Brotli => BrotliOptions,
+    Xz => XzOptions,
+    Lz4 => Lz4Options,
+    Flate => FlateOptions,
+    BinCode => BinCodeOptions
+);
+
+impl From<Quality> for CodecOptions {
+    fn from(quality: Quality) -> Self {
+        CodecOptions::Quality(quality)
+    }
+}
+
+/// Encodes `data` with `codec` using codec-specific settings.
+///
+/// Passing a `Quality` behaves exactly like `encode`. Settings for a
+/// different codec fail with `Error::UnsupportedOptions`.
+pub fn encode_with_options<O>(data: &[u8], codec: Codec, options: O) -> Result<Vec<u8>>
+where
+    O: Into<CodecOptions>,
+{
+    match (codec, options.into()) {
+        (codec, CodecOptions::Quality(quality)) => crate::encode(data, codec, quality),
+
+        #[cfg(feature = "zstd_support")]
+        (Codec::Zstd, CodecOptions::Zstd(options)) => {
+            crate::codecs::zstd::encode_with_options(data, &options)
+        }
+
+        #[cfg(feature = "brotli_support")]
+        (Codec::Brotli, CodecOptions::Brotli(options)) => {
+            crate::codecs::brotli::encode_with_options(data, &options)
+        }
+
+        #[cfg(feature = "xz_support")]
+        (Codec::Xz, CodecOptions::Xz(options)) => {
+            crate::codecs::xz::encode_with_options(data, &options)
+        }
+
+        #[cfg(feature = "xz_support")]
+        (Codec::Lzma, CodecOptions::Xz(options)) => {
+            crate::codecs::lzma::encode_with_options(data, &options)
+        }
+
+        #[cfg(feature = "xz_support")]
+        (Codec::Lzma2Raw, CodecOptions::Xz(options)) => {
+            crate::codecs::lzma::encode_raw2_with_options(data, &options)
+        }
+
+        #[cfg(feature = "lz4_support")]
+        (Codec::Lz4, CodecOptions::Lz4(options)) => {
+            crate::codecs::lz4::encode_with_options(data, &options)
+        }
+
+        #[cfg(feature = "lz4_support")]
+        (Codec::Lz4Block, CodecOptions::Lz4(options)) => {
+            crate::codecs::lz4::encode_block_with_options(data, &options)
+        }
+
+        #[cfg(feature = "bincode_support")]
+        (Codec::BinCode, CodecOptions::BinCode(options)) => {
+            crate::codecs::bincode::encode_with_options(data, &options)
+        }
+
+        (Codec::Gzip, CodecOptions::Flate(options))
+        | (Codec::Deflate, CodecOptions::Flate(options))
+        | (Codec::Zlib, CodecOptions::Flate(options)) => {
+            crate::encode(data, codec, options.quality)
+        }
+
+        (codec, _) if !crate::is_codec_enabled(codec) => Err(Error::CodecDisabled(codec)),
+        (codec, _) => Err(Error::UnsupportedOptions(codec)),
+    }
+}
+
+/// Decodes `data` with `codec` using codec-specific settings.
+///
+/// Only `BinCodeOptions`, the `Lz4Options` of `Lz4Block`, the `window_log`
+/// of `ZstdOptions`, the `dict_size` of `XzOptions` for `Lzma` and
+/// `Lzma2Raw`, and the filters of `XzOptions` for `Lzma2Raw` change how data
+/// is decoded. The other settings of the compression codecs only affect
+/// encoding, so they are accepted and ignored, while settings for a
+/// different codec fail with `Error::UnsupportedOptions`.
+pub fn decode_with_options<O>(data: &[u8], codec: Codec, options: O) -> Result<Vec<u8>>
+where
+    O: Into<CodecOptions>,
+{
+    match (codec, options.into()) {
+        #[cfg(feature = "bincode_support")]
+        (Codec::BinCode, CodecOptions::BinCode(options)) => {
+            crate::codecs::bincode::decode_with_options(data, &options)
+        }
+
+        #[cfg(feature = "lz4_support")]
+        (Codec::Lz4Block, CodecOptions::Lz4(options)) => {
+            crate::codecs::lz4::decode_block_with_options(data, &options)
+        }
+
+        #[cfg(feature = "zstd_support")]
+        (Codec::Zstd, CodecOptions::Zstd(options)) => {
+            crate::codecs::zstd::decode_with_options(data, &options)
+        }
+
+        #[cfg(feature = "xz_support")]
+        (Codec::Lzma, CodecOptions::Xz(options)) => {
+            crate::codecs::lzma::decode_with_options(data, &options)
+        }
+
+        #[cfg(feature = "xz_support")]
+        (Codec::Lzma2Raw, CodecOptions::Xz(options)) => {
+            crate::codecs::lzma::decode_raw2_with_options(data, &options)
+        }
+
+        (codec, _) if !crate::is_codec_enabled(codec) => Err(Error::CodecDisabled(codec)),
+
+        (_, CodecOptions::Quality(_))
+        | (Codec::Brotli, CodecOptions::Brotli(_))
+        | (Codec::Xz, CodecOptions::Xz(_))
+        | (Codec::Lz4, CodecOptions::Lz4(_))
+        | (Codec::Gzip, CodecOptions::Flate(_))
+        | (Codec::Deflate, CodecOptions::Flate(_))
+        | (Codec::Zlib, CodecOptions::Flate(_)) => crate::decode(data, codec),
+
+        (codec, _) => Err(Error::UnsupportedOptions(codec)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const TEST_DATA: &[u8] = include_bytes!("ipsum.txt");
+
+    #[test]
+    fn options_quality_shorthand() {
+        for &codec in &[Codec::Identity, Codec::Zstd, Codec::Brotli, Codec::Gzip] {
+            if is_codec_enabled(codec) {
+                assert_eq!(
+                    encode_with_options(TEST_DATA, codec, Quality::Level3).unwrap(),
+                    encode(TEST_DATA, codec, Quality::Level3).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn options_round_trip() {
+        let cases: &[(Codec, CodecOptions)] = &[
+            (
+                Codec::Zstd,
+                ZstdOptions::new(Quality::Level9)
+                    .long_distance_matching(true)
+                    .window_log(20)
+                    .checksum(true)
+                    .into(),
+            ),
+            (
+                Codec::Brotli,
+                BrotliOptions::new(Quality::Maximum)
+                    .window(24)
+                    .block(18)
+                    .text(true)
+                    .into(),
+            ),
+            (
+                Codec::Xz,
+                XzOptions::new(Quality::Level6)
+                    .dict_size(1 << 20)
+                    .extreme(true)
+                    .into(),
+            ),
+            (
+                Codec::Xz,
+                XzOptions::new(Quality::Level9)
+                    .bcj(XzBcj::X86)
+                    .delta(4)
+                    .check(XzCheck::Sha256)
+                    .literal_context_bits(0)
+                    .literal_position_bits(2)
+                    .position_bits(2)
+                    .nice_len(273)
+                    .into(),
+            ),
+            (
+                Codec::Lz4,
+                Lz4Options::new(Quality::Level9)
+                    .block_size(Lz4BlockSize::Max64KB)
+                    .independent_blocks(true)
+                    .content_checksum(false)
+                    .into(),
+            ),
+            (
+                Codec::Lz4,
+                Lz4Options::new(Quality::Level1)
+                    .mode(Lz4Mode::HighCompression)
+                    .block_checksum(false)
+                    .into(),
+            ),
+            (
+                Codec::Lz4Block,
+                Lz4Options::new(Quality::Level9).mode(Lz4Mode::Fast).into(),
+            ),
+            (Codec::Zlib, FlateOptions::new(Quality::Level1).into()),
+        ];
+        for &(codec, options) in cases.iter().filter(|(codec, _)| is_codec_enabled(*codec)) {
+            let encoded = encode_with_options(TEST_DATA, codec, options).unwrap();
+            assert_eq!(decode(&encoded, codec).unwrap(), TEST_DATA, "{}", codec);
+            assert_eq!(
+                decode_with_options(&encoded, codec, options).unwrap(),
+                TEST_DATA,
+                "{}",
+                codec
+            );
+        }
+    }
+
+    #[cfg(feature = "zstd_support")]
+    #[test]
+    fn options_zstd_window_log() {
+        use std::io::Write;
+        use zstd::stream::raw::{CParameter, Encoder};
+
+        for &window_log in &[10, 27, 28, 31] {
+            let options = ZstdOptions::new(Quality::Level3).window_log(window_log);
+            let encoded = encode_with_options(TEST_DATA, Codec::Zstd, options).unwrap();
+            assert_eq!(decode(&encoded, Codec::Zstd).unwrap(), TEST_DATA);
+            assert_eq!(
+                decode_with_options(&encoded, Codec::Zstd, options).unwrap(),
+                TEST_DATA
+            );
+        }
+        assert!(encode_with_options(
+            TEST_DATA,
+            Codec::Zstd,
+            ZstdOptions::new(Quality::Level3).window_log(32)
+        )
+        .is_err());
+
+        // Streamed frames have no content size, so they keep the full window.
+        let mut encoder = Encoder::new(3).unwrap();
+        encoder.set_parameter(CParameter::WindowLog(28)).unwrap();
+        let mut writer = zstd::stream::zio::Writer::new(Vec::new(), encoder);
+        writer.write_all(TEST_DATA).unwrap();
+        writer.finish().unwrap();
+        let (encoded, _) = writer.into_inner();
+        assert!(decode(&encoded, Codec::Zstd).is_err());
+        let options = ZstdOptions::new(Quality::Level3).window_log(28);
+        assert_eq!(
+            decode_with_options(&encoded, Codec::Zstd, options).unwrap(),
+            TEST_DATA
+        );
+    }
+
+    #[cfg(feature = "lz4_support")]
+    #[test]
+    fn options_lz4_defaults() {
+        const BLOCK_CHECKSUM_FLAG: u8 = 0x10;
+        let encoded = encode(TEST_DATA, Codec::Lz4, Quality::Default).unwrap();
+        assert_eq!(
+            encode_with_options(TEST_DATA, Codec::Lz4, Lz4Options::default()).unwrap(),
+            encoded
+        );
+        assert_eq!(encoded[4] & BLOCK_CHECKSUM_FLAG, BLOCK_CHECKSUM_FLAG);
+
+        let options = Lz4Options::default().block_checksum(false);
+        let encoded = encode_with_options(TEST_DATA, Codec::Lz4, options).unwrap();
+        assert_eq!(encoded[4] & BLOCK_CHECKSUM_FLAG, 0);
+        assert_eq!(decode(&encoded, Codec::Lz4).unwrap(), TEST_DATA);
+    }
+
+    #[cfg(feature = "lz4_support")]
+    #[test]
+    fn options_lz4_block() {
+        let encoded = encode(TEST_DATA, Codec::Lz4Block, Quality::Default).unwrap();
+        assert_eq!(&encoded[..4], &(TEST_DATA.len() as u32).to_le_bytes());
+
+        let options = Lz4Options::new(Quality::Level1).prepend_size(false);
+        let encoded = encode_with_options(TEST_DATA, Codec::Lz4Block, options).unwrap();
+        assert!(decode_with_options(&encoded, Codec::Lz4Block, options).is_err());
+        assert!(decode_with_options(
+            &encoded,
+            Codec::Lz4Block,
+            options.uncompressed_size(u32::MAX)
+        )
+        .is_err());
+        for &size in &[TEST_DATA.len() as u32, 1 << 20] {
+            let options = options.uncompressed_size(size);
+            assert_eq!(
+                decode_with_options(&encoded, Codec::Lz4Block, options).unwrap(),
+                TEST_DATA
+            );
+        }
+
+        let fast = Lz4Options::new(Quality::Maximum).mode(Lz4Mode::Fast);
+        let high = Lz4Options::new(Quality::Maximum).mode(Lz4Mode::HighCompression);
+        assert!(
+            encode_with_options(TEST_DATA, Codec::Lz4Block, high)
+                .unwrap()
+                .len()
+                < encode_with_options(TEST_DATA, Codec::Lz4Block, fast)
+                    .unwrap()
+                    .len()
+        );
+    }
+
+    #[cfg(feature = "xz_support")]
+    #[test]
+    fn options_xz() {
+        use std::io::Read;
+
+        let checks = [
+            (XzCheck::None, 0),
+            (XzCheck::Crc32, 1),
+            (XzCheck::Crc64, 4),
+            (XzCheck::Sha256, 10),
+        ];
+        for &(check, id) in &checks {
+            let encoded =
+                encode_with_options(TEST_DATA, Codec::Xz, XzOptions::default().check(check))
+                    .unwrap();
+            // The second stream flags byte holds the check id.
+            assert_eq!(encoded[7], id, "{:?}", check);
+            assert_eq!(decode(&encoded, Codec::Xz).unwrap(), TEST_DATA);
+        }
+
+        let bcjs = [
+            XzBcj::X86,
+            XzBcj::PowerPc,
+            XzBcj::Ia64,
+            XzBcj::Arm,
+            XzBcj::ArmThumb,
+            XzBcj::Sparc,
+        ];
+        for &bcj in &bcjs {
+            let options = XzOptions::default().bcj(bcj).delta(256);
+            let encoded = encode_with_options(TEST_DATA, Codec::Xz, options).unwrap();
+            assert_eq!(decode(&encoded, Codec::Xz).unwrap(), TEST_DATA, "{:?}", bcj);
+        }
+
+        // The stock xz2 decoder must undo our hand-built delta filter.
+        for &dist in &[1, 2, 4, 256] {
+            let options = XzOptions::default().delta(dist);
+            let encoded = encode_with_options(TEST_DATA, Codec::Xz, options).unwrap();
+            let mut decoded = Vec::new();
+            xz2::read::XzDecoder::new(&encoded[..])
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, TEST_DATA, "delta {}", dist);
+        }
+
+        let invalid = [
+            XzOptions::default().delta(0),
+            XzOptions::default().delta(257),
+            XzOptions::default()
+                .literal_context_bits(3)
+                .literal_position_bits(2),
+            XzOptions::default().nice_len(1),
+        ];
+        for &options in &invalid {
+            assert!(
+                encode_with_options(TEST_DATA, Codec::Xz, options).is_err(),
+                "{:?}",
+                options
+            );
+        }
+    }
+
+    #[cfg(feature = "xz_support")]
+    #[test]
+    fn options_lzma2_raw_dict_size() {
+        let encoded = encode(TEST_DATA, Codec::Lzma2Raw, Quality::Default).unwrap();
+        let options = XzOptions::default().dict_size(128 << 20);
+        assert_eq!(
+            decode_with_options(&encoded, Codec::Lzma2Raw, options).unwrap(),
+            TEST_DATA
+        );
+    }
+
+    #[cfg(feature = "xz_support")]
+    #[test]
+    fn options_lzma() {
+        let options = XzOptions::new(Quality::Level3)
+            .dict_size(1 << 20)
+            .literal_context_bits(0)
+            .position_bits(0)
+            .nice_len(64);
+        for &codec in &[Codec::Lzma, Codec::Lzma2Raw] {
+            let encoded = encode_with_options(TEST_DATA, codec, options).unwrap();
+            assert_ne!(encoded, encode(TEST_DATA, codec, Quality::Level3).unwrap());
+            assert_eq!(decode(&encoded, codec).unwrap(), TEST_DATA, "{}", codec);
+            assert_eq!(
+                decode_with_options(&encoded, codec, options).unwrap(),
+                TEST_DATA,
+                "{}",
+                codec
+            );
+            let invalid = XzOptions::default().nice_len(1);
+            assert!(encode_with_options(TEST_DATA, codec, invalid).is_err());
+        }
+
+        // The LZMA-alone header records the dictionary size, which decoders
+        // check against theirs before allocating anything.
+        let mut encoded = encode_with_options(TEST_DATA, Codec::Lzma, options).unwrap();
+        assert_eq!(&encoded[1..5], &(1u32 << 20).to_le_bytes());
+        let small = XzOptions::default().dict_size(4096);
+        assert!(decode_with_options(&encoded, Codec::Lzma, small).is_err());
+        encoded[1..5].copy_from_slice(&(1u32 << 30).to_le_bytes());
+        assert!(decode(&encoded, Codec::Lzma).is_err());
+
+        // LZMA-alone has no room for filters, while raw streams need the
+        // same filters to decode.
+        let filtered = XzOptions::default().bcj(XzBcj::X86).delta(4);
+        assert!(encode_with_options(TEST_DATA, Codec::Lzma, filtered).is_err());
+        let encoded = encode_with_options(TEST_DATA, Codec::Lzma2Raw, filtered).unwrap();
+        assert_eq!(
+            decode_with_options(&encoded, Codec::Lzma2Raw, filtered).unwrap(),
+            TEST_DATA
+        );
+        assert_ne!(
+            decode(&encoded, Codec::Lzma2Raw).ok().as_deref(),
+            Some(TEST_DATA)
+        );
+    }
+
+    #[cfg(feature = "xz_support")]
+    #[test]
+    fn options_lzma2_raw_decoded_len() {
+        use crate::codecs::lzma::decoded_len_raw2;
+        // Incompressible data is stored in uncompressed chunks.
+        let noise: Vec<u8> = (0..100_000u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+            .collect();
+        for data in &[TEST_DATA, &noise[..], &[]] {
+            let encoded = encode(data, Codec::Lzma2Raw, Quality::Default).unwrap();
+            assert_eq!(decoded_len_raw2(&encoded), Some(data.len() as u64));
+            assert_eq!(decoded_len_raw2(&encoded[..encoded.len() - 1]), None);
+        }
+    }
+
+    #[cfg(feature = "bincode_support")]
+    #[test]
+    fn options_bincode() {
+        let data: &[u8] = &[1, 2, 3];
+        let cases: &[(BinCodeOptions, &[u8])] = &[
+            (
+                BinCodeOptions::default(),
+                &[3, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3],
+            ),
+            (
+                BinCodeOptions::default().endian(BinCodeEndian::Big),
+                &[0, 0, 0, 0, 0, 0, 0, 3, 1, 2, 3],
+            ),
+            (
+                BinCodeOptions::default().int_encoding(BinCodeIntEncoding::Varint),
+                &[3, 1, 2, 3],
+            ),
+        ];
+        for &(options, expected) in cases {
+            let encoded = encode_with_options(data, Codec::BinCode, options).unwrap();
+            assert_eq!(encoded, expected);
+            assert_eq!(
+                decode_with_options(&encoded, Codec::BinCode, options).unwrap(),
+                data
+            );
+        }
+        assert_eq!(
+            encode_with_options(data, Codec::BinCode, Quality::Default).unwrap(),
+            encode(data, Codec::BinCode, Quality::Default).unwrap()
+        );
+
+        let encoded = encode(TEST_DATA, Codec::BinCode, Quality::Default).unwrap();
+        assert!(matches!(
+            decode_with_options(
+                &encoded,
+                Codec::BinCode,
+                BinCodeOptions::default().limit(100)
+            ),
+            Err(Error::LimitExceeded { limit: 100 })
+        ));
+    }
+
+    #[cfg(feature = "zstd_support")]
+    #[test]
+    fn options_mismatch() {
+        assert!(matches!(
+            encode_with_options(TEST_DATA, Codec::Zstd, BrotliOptions::default()),
+            Err(Error::UnsupportedOptions(Codec::Zstd))
+        ));
+    }
+}
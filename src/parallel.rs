@@ -0,0 +1,295 @@
+//! Parallel compression of large inputs split into independent chunks.
+//!
+//! Codecs whose format allows concatenation (gzip members, zstd, lz4 and
+//! snappy frames, bzip2 and xz streams) produce a standard stream that any
+//! decoder for the format accepts. Other codecs produce a chunked container,
+//! which `decode_parallel` decodes on multiple threads:
+//!
+//! | Size | Field                                          |
+//! |------|------------------------------------------------|
+//! | 4    | Magic bytes `SMSP`                             |
+//! | 1    | Format version                                 |
+//! | 4    | Number of chunks (little-endian)               |
+//! | 8*n  | Length of each chunk frame (little-endian)     |
+//!
+//! The chunks follow the lengths, each stored as a `frame` so that it carries
+//! its own codec and checksum.
+
+use crate::{Codec, Error, Quality, Result};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// The magic bytes every chunked container starts with.
+pub const MAGIC: &[u8; 4] = b"SMSP";
+
+const VERSION: u8 = 1;
+const FIXED_HEADER_LEN: usize = 9;
+
+/// Returns true if the codec's output remains valid when concatenated.
+fn is_concatenable(codec: Codec) -> bool {
+    matches!(
+        codec,
+        Codec::Identity
+            | Codec::Gzip
+            | Codec::Bzip2
+            | Codec::Zstd
+            | Codec::Lz4
+            | Codec::Snappy
+            | Codec::Xz
+    )
+}
+
+/// Encodes `data` in chunks of `chunk_size` bytes on up to `threads` threads.
+///
+/// A `threads` value of zero uses the available parallelism of the machine.
+/// Gzip, bzip2, zstd, lz4, snappy and xz output is a standard stream of
+/// concatenated members, while other codecs produce a chunked container that
+/// can be decoded with `decode_parallel`.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is zero.
+pub fn encode_parallel(
+    data: &[u8],
+    codec: Codec,
+    quality: Quality,
+    chunk_size: usize,
+    threads: usize,
+) -> Result<Vec<u8>> {
+    assert!(chunk_size != 0, "chunk size must be non-zero");
+    let chunks: Vec<&[u8]> = if data.is_empty() {
+        vec![data]
+    } else {
+        data.chunks(chunk_size).collect()
+    };
+
+    if is_concatenable(codec) {
+        let encoded = map_parallel(&chunks, threads, |chunk| {
+            crate::encode(chunk, codec, quality)
+        })?;
+        return Ok(encoded.concat());
+    }
+
+    let frames = map_parallel(&chunks, threads, |chunk| {
+        crate::frame::encode(chunk, codec, quality)
+    })?;
+    let mut buf = Vec::with_capacity(
+        FIXED_HEADER_LEN + 8 * frames.len() + frames.iter().map(Vec::len).sum::<usize>(),
+    );
+    buf.extend_from_slice(MAGIC);
+    buf.push(VERSION);
+    buf.extend_from_slice(&(frames.len() as u32).to_le_bytes());
+    for frame in &frames {
+        buf.extend_from_slice(&(frame.len() as u64).to_le_bytes());
+    }
+    for frame in &frames {
+        buf.extend_from_slice(frame);
+    }
+    Ok(buf)
+}
+
+/// Decodes the output of `encode_parallel`.
+///
+/// Chunked containers are decoded on up to `threads` threads, verifying the
+/// codec and checksum of every chunk. Output of codecs that are never chunked,
+/// and any other input, is passed to `decode`.
+pub fn decode_parallel(data: &[u8], codec: Codec, threads: usize) -> Result<Vec<u8>> {
+    if is_concatenable(codec) || !is_chunked(data) {
+        return crate::decode(data, codec);
+    }
+    let chunks = parse(data)?;
+    let decoded = map_parallel(&chunks, threads, |chunk| {
+        let header = crate::frame::header(chunk)?;
+        if header.codec != codec {
+            return Err(Error::InvalidFrame(format!(
+                "codec mismatch - expected {}, found {}",
+                codec, header.codec
+            )));
+        }
+        crate::frame::decode(chunk)
+    })?;
+    Ok(decoded.concat())
+}
+
+/// Returns true if `data` starts with the chunked container magic bytes.
+pub fn is_chunked(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn parse(data: &[u8]) -> Result<Vec<&[u8]>> {
+    let truncated = || Error::InvalidFrame("truncated chunked container".to_string());
+    if data.len() < FIXED_HEADER_LEN {
+        return Err(truncated());
+    }
+    if data[4] != VERSION {
+        return Err(Error::InvalidFrame(format!(
+            "unsupported chunked container version: {}",
+            data[4]
+        )));
+    }
+
+    let mut count = [0u8; 4];
+    count.copy_from_slice(&data[5..9]);
+    let count = u32::from_le_bytes(count) as usize;
+    let lengths_end = count
+        .checked_mul(8)
+        .and_then(|len| len.checked_add(FIXED_HEADER_LEN))
+        .ok_or_else(truncated)?;
+    let lengths = data
+        .get(FIXED_HEADER_LEN..lengths_end)
+        .ok_or_else(truncated)?;
+
+    let mut rest = &data[lengths_end..];
+    let mut chunks = Vec::with_capacity(count);
+    for length in lengths.chunks_exact(8) {
+        let mut len = [0u8; 8];
+        len.copy_from_slice(length);
+        let len = u64::from_le_bytes(len) as usize;
+        if len > rest.len() {
+            return Err(truncated());
+        }
+        let (chunk, tail) = rest.split_at(len);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    Ok(chunks)
+}
+
+/// Applies `f` to every item on up to `threads` threads, preserving order.
+fn map_parallel<F>(items: &[&[u8]], threads: usize, f: F) -> Result<Vec<Vec<u8>>>
+where
+    F: Fn(&[u8]) -> Result<Vec<u8>> + Sync,
+{
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, |threads| threads.get()),
+        threads => threads,
+    }
+    .min(items.len());
+
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, Result<Vec<u8>>)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        match items.get(index) {
+                            Some(item) => results.push((index, f(item))),
+                            None => return results,
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("compression thread panicked"))
+            .collect()
+    });
+
+    results.sort_by_key(|&(index, _)| index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const TEST_DATA: &[u8] = include_bytes!("ipsum.txt");
+
+    #[test]
+    fn parallel_round_trip() {
+        for &codec in ALL_CODECS.iter().filter(|&&codec| is_codec_enabled(codec)) {
+            for &threads in &[0, 1, 4] {
+                let encoded =
+                    encode_parallel(TEST_DATA, codec, Quality::Default, 1000, threads).unwrap();
+                assert_eq!(
+                    parallel::is_chunked(&encoded),
+                    !matches!(
+                        codec,
+                        Codec::Identity
+                            | Codec::Gzip
+                            | Codec::Bzip2
+                            | Codec::Zstd
+                            | Codec::Lz4
+                            | Codec::Snappy
+                            | Codec::Xz
+                    )
+                );
+                assert_eq!(
+                    decode_parallel(&encoded, codec, threads).unwrap(),
+                    TEST_DATA,
+                    "{}",
+                    codec
+                );
+            }
+            let encoded = encode_parallel(&[], codec, Quality::Default, 1000, 4).unwrap();
+            assert!(decode_parallel(&encoded, codec, 4).unwrap().is_empty());
+        }
+    }
+
+    #[test]
+    fn parallel_stream_decode() {
+        use std::io::Read;
+        let codecs = ALL_CODECS.iter().filter(|&&codec| is_codec_enabled(codec));
+        for &codec in codecs.filter(|&&codec| parallel::is_concatenable(codec)) {
+            let encoded = encode_parallel(TEST_DATA, codec, Quality::Default, 1000, 4).unwrap();
+            let mut decoded = Vec::new();
+            Decoder::new(&encoded[..], codec)
+                .unwrap()
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(decoded, TEST_DATA, "{}", codec);
+
+            let options = DecodeOptions::new().max_output_bytes(TEST_DATA.len() as u64);
+            assert_eq!(
+                decode_with(&encoded, codec, &options).unwrap(),
+                TEST_DATA,
+                "{}",
+                codec
+            );
+        }
+    }
+
+    #[cfg(feature = "gzip_support")]
+    #[test]
+    fn parallel_gzip_members() {
+        use std::io::Read;
+        let encoded = encode_parallel(TEST_DATA, Codec::Gzip, Quality::Default, 1000, 4).unwrap();
+        let mut buf = Vec::new();
+        flate2::read::MultiGzDecoder::new(&encoded[..])
+            .read_to_end(&mut buf)
+            .unwrap();
+        assert_eq!(buf, TEST_DATA);
+    }
+
+    #[test]
+    fn parallel_magic_in_payload() {
+        let data = b"SMSP is not a chunked container here";
+        let encoded = encode_parallel(data, Codec::Identity, Quality::Default, 8, 2).unwrap();
+        assert_eq!(
+            decode_parallel(&encoded, Codec::Identity, 2).unwrap(),
+            &data[..]
+        );
+    }
+
+    #[cfg(all(feature = "deflate_support", feature = "zlib_support"))]
+    #[test]
+    fn parallel_codec_mismatch() {
+        let encoded =
+            encode_parallel(TEST_DATA, Codec::Deflate, Quality::Default, 1000, 4).unwrap();
+        assert!(decode_parallel(&encoded, Codec::Zlib, 4).is_err());
+    }
+
+    #[cfg(feature = "deflate_support")]
+    #[test]
+    fn parallel_detects_corruption() {
+        let mut encoded =
+            encode_parallel(TEST_DATA, Codec::Deflate, Quality::Default, 1000, 4).unwrap();
+        assert!(decode_parallel(&encoded[..20], Codec::Deflate, 4).is_err());
+        let last = encoded.len() - 10;
+        encoded[last] ^= 0xff;
+        assert!(decode_parallel(&encoded, Codec::Deflate, 4).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{Codec, Error, Quality};
+use std::{fmt, str};
+
+/// An ordered list of codecs that are applied one after another.
+///
+/// `encode` applies the stages first to last, and `decode` reverses them. A
+/// pipeline can be parsed from a comma separated list of stages, where each
+/// stage is a codec optionally followed by a colon and a quality, e.g.
+/// `"bincode,zstd:level9,base58"`.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Pipeline {
+    stages: Vec<(Codec, Quality)>,
+}
+
+impl Pipeline {
+    /// Creates a pipeline without any stages.
+    pub fn new() -> Self {
+        Pipeline { stages: Vec::new() }
+    }
+
+    /// Appends a stage, returning the pipeline for chaining.
+    pub fn stage(mut self, codec: Codec, quality: Quality) -> Self {
+        self.push(codec, quality);
+        self
+    }
+
+    /// Appends a stage.
+    pub fn push(&mut self, codec: Codec, quality: Quality) {
+        self.stages.push((codec, quality));
+    }
+
+    pub fn stages(&self) -> &[(Codec, Quality)] {
+        &self.stages
+    }
+
+    pub fn encode(&self, data: &[u8]) -> crate::Result<Vec<u8>> {
+        let mut buf = data.to_vec();
+        for &(codec, quality) in &self.stages {
+            buf = crate::encode(&buf, codec, quality)?;
+        }
+        Ok(buf)
+    }
+
+    pub fn decode(&self, data: &[u8]) -> crate::Result<Vec<u8>> {
+        let mut buf = data.to_vec();
+        for &(codec, _) in self.stages.iter().rev() {
+            buf = crate::decode(&buf, codec)?;
+        }
+        Ok(buf)
+    }
+}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, (codec, quality)) in self.stages.iter().enumerate() {
+            if index > 0 {
+                f.write_str(",")?;
+            }
+            match quality {
+                Quality::Default => write!(f, "{}", codec)?,
+                quality => write!(f, "{}:{}", codec, quality)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl str::FromStr for Pipeline {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut pipeline = Pipeline::new();
+        if s.trim().is_empty() {
+            return Ok(pipeline);
+        }
+
+        for stage in s.split(',').map(str::trim) {
+            let (codec, quality) = match stage.split_once(':') {
+                Some((codec, quality)) => (codec.trim(), quality.trim().parse()?),
+                None => (stage, Quality::Default),
+            };
+            if codec.is_empty() {
+                return Err(Error::UnknownCodec(stage.to_string()));
+            }
+            pipeline.push(codec.parse()?, quality);
+        }
+
+        Ok(pipeline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const TEST_DATA: &[u8] = include_bytes!("ipsum.txt");
+
+    #[test]
+    fn pipeline_parse() {
+        let pipeline: Pipeline = "bincode, zstd:level9,base58".parse().unwrap();
+        assert_eq!(
+            pipeline.stages(),
+            &[
+                (Codec::BinCode, Quality::Default),
+                (Codec::Zstd, Quality::Level9),
+                (Codec::Base58, Quality::Default),
+            ]
+        );
+        assert_eq!(pipeline.to_string(), "bincode,zstd:level9,base58");
+        assert!("".parse::<Pipeline>().unwrap().stages().is_empty());
+        assert!("zstd,,base58".parse::<Pipeline>().is_err());
+        assert!("zstd:level42".parse::<Pipeline>().is_err());
+    }
+
+    #[test]
+    fn pipeline_identity() {
+        let pipeline = Pipeline::new();
+        assert_eq!(pipeline.encode(TEST_DATA).unwrap(), TEST_DATA);
+        assert_eq!(pipeline.decode(TEST_DATA).unwrap(), TEST_DATA);
+    }
+
+    #[cfg(all(
+        feature = "bincode_support",
+        feature = "zstd_support",
+        feature = "base58_support"
+    ))]
+    #[test]
+    fn pipeline_round_trip() {
+        let pipeline = Pipeline::new()
+            .stage(Codec::BinCode, Quality::Default)
+            .stage(Codec::Zstd, Quality::Level9)
+            .stage(Codec::Base58, Quality::Default);
+        let encoded = pipeline.encode(TEST_DATA).unwrap();
+        let expected = encode(
+            &encode(
+                &encode(TEST_DATA, Codec::BinCode, Quality::Default).unwrap(),
+                Codec::Zstd,
+                Quality::Level9,
+            )
+            .unwrap(),
+            Codec::Base58,
+            Quality::Default,
+        )
+        .unwrap();
+        assert_eq!(encoded, expected);
+        assert_eq!(pipeline.decode(&encoded).unwrap(), TEST_DATA);
+    }
+}
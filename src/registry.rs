@@ -0,0 +1,271 @@
+use crate::{Codec, Error, Quality, Result};
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock, RwLock},
+};
+
+/// The codecs built into this crate, in the order they are registered.
+pub(crate) const BUILTIN_CODECS: &[Codec] = &[
+    Codec::Identity,
+    Codec::Gzip,
+    Codec::Deflate,
+    Codec::Zlib,
+    Codec::Zstd,
+    Codec::Brotli,
+    Codec::Bzip2,
+    Codec::Lz4,
+    Codec::Lz4Block,
+    Codec::Snappy,
+    Codec::SnappyRaw,
+    Codec::Xz,
+    Codec::Lzma,
+    Codec::Lzma2Raw,
+    Codec::BinCode,
+    Codec::Base58,
+    Codec::Base58Ripple,
+    Codec::Base58Flickr,
+    Codec::Base58Check,
+    Codec::Base58CheckRipple,
+    Codec::Base58CheckFlickr,
+    Codec::Base64,
+    Codec::Base64Url,
+    Codec::Base64NoPad,
+    Codec::Base64UrlNoPad,
+    Codec::Base32,
+    Codec::Base32Crockford,
+    Codec::Hex,
+];
+
+/// Describes what a codec implementation is able to do.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Capabilities {
+    /// The codec is intended to reduce the size of its input.
+    pub compression: bool,
+
+    /// The encoded output is printable text.
+    pub text: bool,
+
+    /// The `Quality` passed to `encode` affects the output.
+    pub quality: bool,
+}
+
+/// An implementation of a codec that can be added to a `Registry`.
+pub trait CodecImpl: Send + Sync {
+    /// The unique name used to look the codec up, e.g. by `Codec::from_str`.
+    fn name(&self) -> &'static str;
+
+    fn encode(&self, data: &[u8], quality: Quality) -> Result<Vec<u8>>;
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>>;
+
+    fn capabilities(&self) -> Capabilities;
+}
+
+/// Exposes one of the codecs built into this crate as a `CodecImpl`.
+struct Builtin(Codec);
+
+impl CodecImpl for Builtin {
+    fn name(&self) -> &'static str {
+        self.0.name()
+    }
+
+    fn encode(&self, data: &[u8], quality: Quality) -> Result<Vec<u8>> {
+        crate::encode(data, self.0, quality)
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<u8>> {
+        crate::decode(data, self.0)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        match self.0 {
+            Codec::Identity | Codec::BinCode => Capabilities::default(),
+            Codec::Base58
+            | Codec::Base58Ripple
+            | Codec::Base58Flickr
+            | Codec::Base58Check
+            | Codec::Base58CheckRipple
+            | Codec::Base58CheckFlickr
+            | Codec::Base64
+            | Codec::Base64Url
+            | Codec::Base64NoPad
+            | Codec::Base64UrlNoPad
+            | Codec::Base32
+            | Codec::Base32Crockford
+            | Codec::Hex => Capabilities {
+                text: true,
+                ..Default::default()
+            },
+            Codec::Snappy | Codec::SnappyRaw => Capabilities {
+                compression: true,
+                ..Default::default()
+            },
+            _ => Capabilities {
+                compression: true,
+                quality: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// A set of codec implementations that can be looked up by name.
+#[derive(Clone)]
+pub struct Registry {
+    codecs: HashMap<&'static str, Arc<dyn CodecImpl>>,
+}
+
+impl Registry {
+    /// Creates a registry containing every built-in codec that was enabled.
+    pub fn new() -> Self {
+        let mut registry = Registry::empty();
+        for &codec in BUILTIN_CODECS {
+            if crate::is_codec_enabled(codec) {
+                registry
+                    .codecs
+                    .insert(codec.name(), Arc::new(Builtin(codec)));
+            }
+        }
+        registry
+    }
+
+    /// Creates a registry without any codecs.
+    pub fn empty() -> Self {
+        Registry {
+            codecs: HashMap::new(),
+        }
+    }
+
+    /// Adds a codec, failing if one with the same name is already registered.
+    pub fn register<C: CodecImpl + 'static>(&mut self, codec: C) -> Result<Codec> {
+        let name = codec.name();
+        if self.codecs.contains_key(name) {
+            return Err(Error::AlreadyRegistered(name));
+        }
+        self.codecs.insert(name, Arc::new(codec));
+        Ok(Codec::Custom(name))
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn CodecImpl>> {
+        self.codecs.get(name).cloned()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.codecs.contains_key(name)
+    }
+
+    /// Returns the names of all registered codecs, sorted alphabetically.
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.codecs.keys().copied().collect();
+        names.sort_unstable();
+        names
+    }
+
+    pub fn encode(&self, data: &[u8], codec: Codec, quality: Quality) -> Result<Vec<u8>> {
+        self.lookup(codec)?.encode(data, quality)
+    }
+
+    pub fn decode(&self, data: &[u8], codec: Codec) -> Result<Vec<u8>> {
+        self.lookup(codec)?.decode(data)
+    }
+
+    fn lookup(&self, codec: Codec) -> Result<Arc<dyn CodecImpl>> {
+        self.get(codec.name())
+            .ok_or_else(|| Error::UnknownCodec(codec.name().to_string()))
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry::new()
+    }
+}
+
+fn global() -> &'static RwLock<Registry> {
+    static GLOBAL: OnceLock<RwLock<Registry>> = OnceLock::new();
+    GLOBAL.get_or_init(|| RwLock::new(Registry::new()))
+}
+
+/// Returns a snapshot of the process-wide registry.
+pub fn registry() -> Registry {
+    global().read().unwrap().clone()
+}
+
+/// Adds a codec to the process-wide registry used by `encode`, `decode` and
+/// `Codec::from_str`, returning the `Codec` that refers to it.
+pub fn register_codec<C: CodecImpl + 'static>(codec: C) -> Result<Codec> {
+    if BUILTIN_CODECS
+        .iter()
+        .any(|builtin| builtin.name() == codec.name())
+    {
+        return Err(Error::AlreadyRegistered(codec.name()));
+    }
+    global().write().unwrap().register(codec)
+}
+
+pub(crate) fn lookup_custom(name: &str) -> Option<Arc<dyn CodecImpl>> {
+    global().read().unwrap().get(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const TEST_DATA: &[u8] = include_bytes!("ipsum.txt");
+
+    struct Reverse;
+
+    impl CodecImpl for Reverse {
+        fn name(&self) -> &'static str {
+            "reverse"
+        }
+
+        fn encode(&self, data: &[u8], _: Quality) -> crate::Result<Vec<u8>> {
+            Ok(data.iter().rev().copied().collect())
+        }
+
+        fn decode(&self, data: &[u8]) -> crate::Result<Vec<u8>> {
+            Ok(data.iter().rev().copied().collect())
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities::default()
+        }
+    }
+
+    #[test]
+    fn registry_builtins() {
+        let registry = Registry::new();
+        assert!(registry.contains("identity"));
+        assert_eq!(registry.contains("zstd"), is_codec_enabled(Codec::Zstd));
+        let encoded = registry
+            .encode(TEST_DATA, Codec::Identity, Quality::Default)
+            .unwrap();
+        assert_eq!(
+            registry.decode(&encoded, Codec::Identity).unwrap(),
+            TEST_DATA
+        );
+    }
+
+    #[test]
+    fn registry_rejects_duplicates() {
+        let mut registry = Registry::empty();
+        assert_eq!(
+            registry.register(Reverse).unwrap(),
+            Codec::Custom("reverse")
+        );
+        assert!(registry.register(Reverse).is_err());
+    }
+
+    #[test]
+    fn register_custom_codec() {
+        let codec = register_codec(Reverse).unwrap();
+        assert_eq!("reverse".parse::<Codec>().unwrap(), codec);
+        assert_eq!(codec.to_string(), "reverse");
+        assert!(is_codec_enabled(codec));
+        let encoded = encode(TEST_DATA, codec, Quality::Default).unwrap();
+        assert_eq!(decode(&encoded, codec).unwrap(), TEST_DATA);
+        assert!(register_codec(Reverse).is_err());
+        assert!(registry().contains("reverse"));
+    }
+}
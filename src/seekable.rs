@@ -0,0 +1,297 @@
+//! The zstd seekable format, which splits the input into independent frames
+//! and appends a seek table so that any byte range can be decoded without
+//! decoding the frames before it.
+//!
+//! The seek table is stored in a skippable frame, so the output remains a
+//! valid zstd stream for decoders unaware of the format:
+//!
+//! | Size | Field                                               |
+//! |------|-----------------------------------------------------|
+//! | 4    | Skippable frame magic `0x184D2A5E` (little-endian)  |
+//! | 4    | Size of the rest of the frame (little-endian)       |
+//! | 8*n  | Compressed and decompressed size of each frame      |
+//! | 4    | Number of frames (little-endian)                    |
+//! | 1    | Descriptor, where bit 7 flags a checksum per entry  |
+//! | 4    | Seekable magic `0x8F92EAB1` (little-endian)         |
+//!
+//! Checksums are never written, and are skipped when reading tables that
+//! contain them.
+
+use crate::{codecs, Codec, Error, Quality, Result};
+use std::convert::TryFrom;
+use std::io::{self, Read, Seek, SeekFrom};
+
+const SEEK_TABLE_MAGIC: u32 = 0x184d_2a5e;
+const SEEKABLE_MAGIC: u32 = 0x8f92_eab1;
+const SKIPPABLE_HEADER_LEN: u64 = 8;
+const FOOTER_LEN: u64 = 9;
+const CHECKSUM_FLAG: u8 = 0x80;
+const RESERVED_BITS: u8 = 0x7c;
+
+/// Encodes `data` with zstd in the seekable format, compressing every
+/// `frame_size` bytes as an independent frame.
+///
+/// Smaller frames allow reads closer to the requested range at the cost of
+/// the compression ratio.
+///
+/// # Panics
+///
+/// Panics if `frame_size` is zero or larger than `u32::MAX`.
+pub fn encode_seekable(data: &[u8], quality: Quality, frame_size: usize) -> Result<Vec<u8>> {
+    assert!(
+        frame_size != 0 && frame_size <= u32::MAX as usize,
+        "frame size must be non-zero and fit in 32 bits"
+    );
+    let mut buf = Vec::new();
+    let mut entries = Vec::new();
+    for chunk in data.chunks(frame_size) {
+        let frame = codecs::zstd::encode(chunk, quality)?;
+        let compressed_len = u32::try_from(frame.len())
+            .map_err(|_| too_large("compressed frame is larger than 4 GiB"))?;
+        entries.push((compressed_len, chunk.len() as u32));
+        buf.extend(frame);
+    }
+
+    let table_len = 8 * entries.len() as u64 + FOOTER_LEN;
+    let table_len =
+        u32::try_from(table_len).map_err(|_| too_large("seek table is larger than 4 GiB"))?;
+    buf.extend_from_slice(&SEEK_TABLE_MAGIC.to_le_bytes());
+    buf.extend_from_slice(&table_len.to_le_bytes());
+    for (compressed_len, decompressed_len) in &entries {
+        buf.extend_from_slice(&compressed_len.to_le_bytes());
+        buf.extend_from_slice(&decompressed_len.to_le_bytes());
+    }
+    buf.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    buf.push(0);
+    buf.extend_from_slice(&SEEKABLE_MAGIC.to_le_bytes());
+    Ok(buf)
+}
+
+/// The location of one frame, in both the compressed and decoded streams.
+#[derive(Copy, Clone, Debug)]
+struct Entry {
+    compressed_offset: u64,
+    compressed_len: u64,
+    decompressed_offset: u64,
+    decompressed_len: u64,
+}
+
+/// Decodes a stream written by `encode_seekable`, decoding only the frames
+/// that contain the bytes being read.
+///
+/// The most recently decoded frame is cached, so sequential reads decode
+/// each frame once.
+pub struct SeekableDecoder<R> {
+    reader: R,
+    entries: Vec<Entry>,
+    len: u64,
+    position: u64,
+    cached: Option<(usize, Vec<u8>)>,
+}
+
+impl<R: Read + Seek> SeekableDecoder<R> {
+    /// Reads the seek table from the end of `reader`.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let end = reader.seek(SeekFrom::End(0))?;
+        if end < SKIPPABLE_HEADER_LEN + FOOTER_LEN {
+            return Err(corrupt("missing seek table"));
+        }
+        let mut footer = [0u8; FOOTER_LEN as usize];
+        reader.seek(SeekFrom::Start(end - FOOTER_LEN))?;
+        reader.read_exact(&mut footer)?;
+        if read_u32(&footer[5..9]) != SEEKABLE_MAGIC {
+            return Err(corrupt("missing seek table"));
+        }
+        let count = u64::from(read_u32(&footer[0..4]));
+        let descriptor = footer[4];
+        if descriptor & RESERVED_BITS != 0 {
+            return Err(corrupt("reserved seek table bits are set"));
+        }
+        let entry_len = if descriptor & CHECKSUM_FLAG != 0 {
+            12
+        } else {
+            8
+        };
+
+        let table_len = entry_len * count + FOOTER_LEN;
+        let table_start = (end - FOOTER_LEN)
+            .checked_sub(entry_len * count + SKIPPABLE_HEADER_LEN)
+            .ok_or_else(|| corrupt("truncated seek table"))?;
+        let mut table = vec![0u8; (SKIPPABLE_HEADER_LEN + table_len - FOOTER_LEN) as usize];
+        reader.seek(SeekFrom::Start(table_start))?;
+        reader.read_exact(&mut table)?;
+        if read_u32(&table[0..4]) != SEEK_TABLE_MAGIC
+            || u64::from(read_u32(&table[4..8])) != table_len
+        {
+            return Err(corrupt("invalid seek table header"));
+        }
+
+        let mut entries = Vec::with_capacity(count as usize);
+        let (mut compressed_offset, mut decompressed_offset) = (0, 0);
+        for entry in table[SKIPPABLE_HEADER_LEN as usize..].chunks_exact(entry_len as usize) {
+            let compressed_len = u64::from(read_u32(&entry[0..4]));
+            let decompressed_len = u64::from(read_u32(&entry[4..8]));
+            entries.push(Entry {
+                compressed_offset,
+                compressed_len,
+                decompressed_offset,
+                decompressed_len,
+            });
+            compressed_offset += compressed_len;
+            decompressed_offset += decompressed_len;
+        }
+        if compressed_offset != table_start {
+            return Err(corrupt("seek table does not match the frames"));
+        }
+
+        Ok(SeekableDecoder {
+            reader,
+            entries,
+            len: decompressed_offset,
+            position: 0,
+            cached: None,
+        })
+    }
+
+    /// The decoded length of the stream.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of independent frames in the stream.
+    pub fn frame_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Decodes the frame at `index`, unless it is already cached.
+    fn frame(&mut self, index: usize) -> io::Result<&[u8]> {
+        if !matches!(self.cached, Some((cached, _)) if cached == index) {
+            let entry = self.entries[index];
+            let mut compressed = vec![0u8; entry.compressed_len as usize];
+            self.reader.seek(SeekFrom::Start(entry.compressed_offset))?;
+            self.reader.read_exact(&mut compressed)?;
+            let frame = codecs::zstd::decode(&compressed)?;
+            if frame.len() as u64 != entry.decompressed_len {
+                return Err(corrupt("frame length does not match the seek table").into());
+            }
+            self.cached = Some((index, frame));
+        }
+        match &self.cached {
+            Some((_, frame)) => Ok(frame),
+            None => unreachable!(),
+        }
+    }
+}
+
+impl<R: Read + Seek> Read for SeekableDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.len {
+            return Ok(0);
+        }
+        let position = self.position;
+        let index = self.entries.partition_point(|entry| {
+            entry.decompressed_offset + entry.decompressed_len <= position
+        });
+        let start = (position - self.entries[index].decompressed_offset) as usize;
+        let frame = &self.frame(index)?[start..];
+        let len = frame.len().min(buf.len());
+        buf[..len].copy_from_slice(&frame[..len]);
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl<R: Read + Seek> Seek for SeekableDecoder<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn corrupt(details: &str) -> Error {
+    Error::corrupt(Codec::Zstd, details)
+}
+
+fn too_large(details: &str) -> Error {
+    Error::Io(io::Error::new(io::ErrorKind::InvalidInput, details))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    const TEST_DATA: &[u8] = include_bytes!("ipsum.txt");
+
+    #[test]
+    fn seekable_round_trip() {
+        let encoded = encode_seekable(TEST_DATA, Quality::Default, 1000).unwrap();
+        assert_eq!(decode(&encoded, Codec::Zstd).unwrap(), TEST_DATA);
+
+        let mut decoder = SeekableDecoder::new(Cursor::new(&encoded)).unwrap();
+        assert_eq!(decoder.len(), TEST_DATA.len() as u64);
+        assert_eq!(decoder.frame_count(), TEST_DATA.len().div_ceil(1000));
+        let mut decoded = Vec::new();
+        decoder.read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, TEST_DATA);
+
+        for &(start, len) in &[(0, 10), (995, 10), (2500, 3000), (TEST_DATA.len() - 5, 5)] {
+            decoder.seek(SeekFrom::Start(start as u64)).unwrap();
+            let mut buf = vec![0u8; len];
+            decoder.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, &TEST_DATA[start..start + len]);
+        }
+
+        assert_eq!(
+            decoder.seek(SeekFrom::End(-3)).unwrap(),
+            TEST_DATA.len() as u64 - 3
+        );
+        assert_eq!(
+            decoder.seek(SeekFrom::Current(1)).unwrap(),
+            TEST_DATA.len() as u64 - 2
+        );
+        assert!(decoder
+            .seek(SeekFrom::Current(-(TEST_DATA.len() as i64)))
+            .is_err());
+        decoder.seek(SeekFrom::End(10)).unwrap();
+        assert_eq!(decoder.read(&mut [0u8; 4]).unwrap(), 0);
+
+        let encoded = encode_seekable(&[], Quality::Default, 1000).unwrap();
+        let decoder = SeekableDecoder::new(Cursor::new(&encoded)).unwrap();
+        assert!(decoder.is_empty());
+        assert_eq!(decoder.frame_count(), 0);
+    }
+
+    #[test]
+    fn seekable_invalid() {
+        let encoded = encode(TEST_DATA, Codec::Zstd, Quality::Default).unwrap();
+        assert!(SeekableDecoder::new(Cursor::new(&encoded)).is_err());
+        let encoded = encode_seekable(TEST_DATA, Quality::Default, 1000).unwrap();
+        assert!(SeekableDecoder::new(Cursor::new(&encoded[100..])).is_err());
+    }
+}
@@ -0,0 +1,552 @@
+#[cfg(any(
+    feature = "brotli_support",
+    feature = "bzip2_support",
+    feature = "deflate_support",
+    feature = "gzip_support",
+    feature = "lz4_support",
+    feature = "snappy_support",
+    feature = "xz_support",
+    feature = "zlib_support",
+    feature = "zstd_support",
+))]
+use crate::codecs;
+use crate::{Codec, Error, Quality};
+use std::io::{self, Read, Write};
+
+/// Wraps a writer and encodes everything written to it with a codec.
+///
+/// `finish` must be called once all data has been written, otherwise the
+/// trailing bytes of the encoded stream may never reach the inner writer.
+///
+/// Text encodings, `Lz4Block`, `Lzma2Raw`, `SnappyRaw`, `BinCode` and custom
+/// codecs are not streaming formats, so data written to an encoder using them
+/// is buffered in memory until `finish` is called.
+pub struct Encoder<W: Write> {
+    inner: EncoderInner<W>,
+}
+
+#[allow(clippy::large_enum_variant)]
+enum EncoderInner<W: Write> {
+    Identity(W),
+
+    #[cfg(feature = "gzip_support")]
+    Gzip(flate2::write::GzEncoder<W>),
+
+    #[cfg(feature = "deflate_support")]
+    Deflate(flate2::write::DeflateEncoder<W>),
+
+    #[cfg(feature = "zlib_support")]
+    Zlib(flate2::write::ZlibEncoder<W>),
+
+    #[cfg(feature = "zstd_support")]
+    Zstd(zstd::stream::write::Encoder<W>),
+
+    #[cfg(feature = "brotli_support")]
+    Brotli(brotli::CompressorWriter<W>),
+
+    #[cfg(feature = "bzip2_support")]
+    Bzip2(bzip2::write::BzEncoder<W>),
+
+    #[cfg(feature = "lz4_support")]
+    Lz4(lz4::Encoder<W>),
+
+    #[cfg(feature = "snappy_support")]
+    Snappy(snap::write::FrameEncoder<W>),
+
+    #[cfg(feature = "xz_support")]
+    Xz(xz2::write::XzEncoder<W>),
+
+    Buffered {
+        codec: Codec,
+        quality: Quality,
+        buffer: Vec<u8>,
+        writer: W,
+    },
+}
+
+impl<W: Write> Encoder<W> {
+    /// Creates an encoder that writes data encoded with `codec` to `writer`.
+    pub fn new(writer: W, codec: Codec, quality: Quality) -> crate::Result<Self> {
+        let inner = match codec {
+            Codec::Identity => EncoderInner::Identity(writer),
+
+            #[cfg(feature = "gzip_support")]
+            Codec::Gzip => EncoderInner::Gzip(codecs::gzip::encoder(writer, quality)?),
+
+            #[cfg(feature = "deflate_support")]
+            Codec::Deflate => EncoderInner::Deflate(codecs::deflate::encoder(writer, quality)?),
+
+            #[cfg(feature = "zlib_support")]
+            Codec::Zlib => EncoderInner::Zlib(codecs::zlib::encoder(writer, quality)?),
+
+            #[cfg(feature = "zstd_support")]
+            Codec::Zstd => EncoderInner::Zstd(codecs::zstd::encoder(writer, quality)?),
+
+            #[cfg(feature = "brotli_support")]
+            Codec::Brotli => EncoderInner::Brotli(codecs::brotli::encoder(writer, quality)?),
+
+            #[cfg(feature = "bzip2_support")]
+            Codec::Bzip2 => EncoderInner::Bzip2(codecs::bzip2::encoder(writer, quality)?),
+
+            #[cfg(feature = "lz4_support")]
+            Codec::Lz4 => EncoderInner::Lz4(codecs::lz4::encoder(writer, quality)?),
+
+            #[cfg(feature = "lz4_support")]
+            Codec::Lz4Block => EncoderInner::Buffered {
+                codec,
+                quality,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            #[cfg(feature = "snappy_support")]
+            Codec::Snappy => EncoderInner::Snappy(codecs::snappy::encoder(writer, quality)?),
+
+            #[cfg(feature = "snappy_support")]
+            Codec::SnappyRaw => EncoderInner::Buffered {
+                codec,
+                quality,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            #[cfg(feature = "xz_support")]
+            Codec::Xz => EncoderInner::Xz(codecs::xz::encoder(writer, quality)?),
+
+            #[cfg(feature = "xz_support")]
+            Codec::Lzma => EncoderInner::Xz(codecs::lzma::encoder(writer, quality)?),
+
+            #[cfg(feature = "xz_support")]
+            Codec::Lzma2Raw => EncoderInner::Buffered {
+                codec,
+                quality,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            #[cfg(feature = "bincode_support")]
+            Codec::BinCode => EncoderInner::Buffered {
+                codec,
+                quality,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            #[cfg(feature = "base58_support")]
+            Codec::Base58
+            | Codec::Base58Ripple
+            | Codec::Base58Flickr
+            | Codec::Base58Check
+            | Codec::Base58CheckRipple
+            | Codec::Base58CheckFlickr => EncoderInner::Buffered {
+                codec,
+                quality,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            #[cfg(feature = "base64_support")]
+            Codec::Base64 | Codec::Base64Url | Codec::Base64NoPad | Codec::Base64UrlNoPad => {
+                EncoderInner::Buffered {
+                    codec,
+                    quality,
+                    buffer: Vec::new(),
+                    writer,
+                }
+            }
+
+            #[cfg(feature = "base32_support")]
+            Codec::Base32 | Codec::Base32Crockford => EncoderInner::Buffered {
+                codec,
+                quality,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            #[cfg(feature = "hex_support")]
+            Codec::Hex => EncoderInner::Buffered {
+                codec,
+                quality,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            Codec::Custom(_) => EncoderInner::Buffered {
+                codec,
+                quality,
+                buffer: Vec::new(),
+                writer,
+            },
+
+            disabled => return Err(Error::CodecDisabled(disabled)),
+        };
+
+        Ok(Encoder { inner })
+    }
+
+    /// Acquires a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        match &self.inner {
+            EncoderInner::Identity(writer) => writer,
+
+            #[cfg(feature = "gzip_support")]
+            EncoderInner::Gzip(encoder) => encoder.get_ref(),
+
+            #[cfg(feature = "deflate_support")]
+            EncoderInner::Deflate(encoder) => encoder.get_ref(),
+
+            #[cfg(feature = "zlib_support")]
+            EncoderInner::Zlib(encoder) => encoder.get_ref(),
+
+            #[cfg(feature = "zstd_support")]
+            EncoderInner::Zstd(encoder) => encoder.get_ref(),
+
+            #[cfg(feature = "brotli_support")]
+            EncoderInner::Brotli(encoder) => encoder.get_ref(),
+
+            #[cfg(feature = "bzip2_support")]
+            EncoderInner::Bzip2(encoder) => encoder.get_ref(),
+
+            #[cfg(feature = "lz4_support")]
+            EncoderInner::Lz4(encoder) => encoder.writer(),
+
+            #[cfg(feature = "snappy_support")]
+            EncoderInner::Snappy(encoder) => encoder.get_ref(),
+
+            #[cfg(feature = "xz_support")]
+            EncoderInner::Xz(encoder) => encoder.get_ref(),
+
+            EncoderInner::Buffered { writer, .. } => writer,
+        }
+    }
+
+    /// Writes any remaining encoded data and returns the underlying writer.
+    pub fn finish(self) -> io::Result<W> {
+        let mut writer = match self.inner {
+            EncoderInner::Identity(writer) => writer,
+
+            #[cfg(feature = "gzip_support")]
+            EncoderInner::Gzip(encoder) => encoder.finish()?,
+
+            #[cfg(feature = "deflate_support")]
+            EncoderInner::Deflate(encoder) => encoder.finish()?,
+
+            #[cfg(feature = "zlib_support")]
+            EncoderInner::Zlib(encoder) => encoder.finish()?,
+
+            #[cfg(feature = "zstd_support")]
+            EncoderInner::Zstd(encoder) => encoder.finish()?,
+
+            #[cfg(feature = "brotli_support")]
+            EncoderInner::Brotli(encoder) => encoder.into_inner(),
+
+            #[cfg(feature = "bzip2_support")]
+            EncoderInner::Bzip2(encoder) => encoder.finish()?,
+
+            #[cfg(feature = "lz4_support")]
+            EncoderInner::Lz4(encoder) => {
+                let (writer, result) = encoder.finish();
+                result?;
+                writer
+            }
+
+            #[cfg(feature = "snappy_support")]
+            EncoderInner::Snappy(encoder) => {
+                encoder.into_inner().map_err(|err| err.into_error())?
+            }
+
+            #[cfg(feature = "xz_support")]
+            EncoderInner::Xz(encoder) => encoder.finish()?,
+
+            EncoderInner::Buffered {
+                codec,
+                quality,
+                buffer,
+                mut writer,
+            } => {
+                writer.write_all(&crate::encode(&buffer, codec, quality)?)?;
+                writer
+            }
+        };
+        writer.flush()?;
+        Ok(writer)
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            EncoderInner::Identity(writer) => writer.write(buf),
+
+            #[cfg(feature = "gzip_support")]
+            EncoderInner::Gzip(encoder) => encoder.write(buf),
+
+            #[cfg(feature = "deflate_support")]
+            EncoderInner::Deflate(encoder) => encoder.write(buf),
+
+            #[cfg(feature = "zlib_support")]
+            EncoderInner::Zlib(encoder) => encoder.write(buf),
+
+            #[cfg(feature = "zstd_support")]
+            EncoderInner::Zstd(encoder) => encoder.write(buf),
+
+            #[cfg(feature = "brotli_support")]
+            EncoderInner::Brotli(encoder) => encoder.write(buf),
+
+            #[cfg(feature = "bzip2_support")]
+            EncoderInner::Bzip2(encoder) => encoder.write(buf),
+
+            #[cfg(feature = "lz4_support")]
+            EncoderInner::Lz4(encoder) => encoder.write(buf),
+
+            #[cfg(feature = "snappy_support")]
+            EncoderInner::Snappy(encoder) => encoder.write(buf),
+
+            #[cfg(feature = "xz_support")]
+            EncoderInner::Xz(encoder) => encoder.write(buf),
+
+            EncoderInner::Buffered { buffer, .. } => buffer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.inner {
+            EncoderInner::Identity(writer) => writer.flush(),
+
+            #[cfg(feature = "gzip_support")]
+            EncoderInner::Gzip(encoder) => encoder.flush(),
+
+            #[cfg(feature = "deflate_support")]
+            EncoderInner::Deflate(encoder) => encoder.flush(),
+
+            #[cfg(feature = "zlib_support")]
+            EncoderInner::Zlib(encoder) => encoder.flush(),
+
+            #[cfg(feature = "zstd_support")]
+            EncoderInner::Zstd(encoder) => encoder.flush(),
+
+            #[cfg(feature = "brotli_support")]
+            EncoderInner::Brotli(encoder) => encoder.flush(),
+
+            #[cfg(feature = "bzip2_support")]
+            EncoderInner::Bzip2(encoder) => encoder.flush(),
+
+            #[cfg(feature = "lz4_support")]
+            EncoderInner::Lz4(encoder) => encoder.flush(),
+
+            #[cfg(feature = "snappy_support")]
+            EncoderInner::Snappy(encoder) => encoder.flush(),
+
+            #[cfg(feature = "xz_support")]
+            EncoderInner::Xz(encoder) => encoder.flush(),
+
+            // Nothing can be emitted until the whole input is known.
+            EncoderInner::Buffered { .. } => Ok(()),
+        }
+    }
+}
+
+/// Wraps a reader and decodes everything read from it with a codec.
+///
+/// Text encodings, `Lz4Block`, `SnappyRaw`, `BinCode` and custom codecs are
+/// not streaming formats, so a decoder using them reads and decodes the whole
+/// inner reader on construction.
+pub struct Decoder<R: Read> {
+    inner: DecoderInner<R>,
+}
+
+#[allow(clippy::large_enum_variant)]
+enum DecoderInner<R: Read> {
+    Identity(R),
+
+    #[cfg(feature = "gzip_support")]
+    Gzip(flate2::read::MultiGzDecoder<R>),
+
+    #[cfg(feature = "deflate_support")]
+    Deflate(flate2::read::DeflateDecoder<R>),
+
+    #[cfg(feature = "zlib_support")]
+    Zlib(flate2::read::ZlibDecoder<R>),
+
+    #[cfg(feature = "zstd_support")]
+    Zstd(zstd::stream::read::Decoder<io::BufReader<R>>),
+
+    #[cfg(feature = "brotli_support")]
+    Brotli(brotli::Decompressor<R>),
+
+    #[cfg(feature = "bzip2_support")]
+    Bzip2(bzip2::read::MultiBzDecoder<R>),
+
+    #[cfg(feature = "lz4_support")]
+    Lz4(codecs::lz4::MultiDecoder<R>),
+
+    #[cfg(feature = "snappy_support")]
+    Snappy(snap::read::FrameDecoder<R>),
+
+    #[cfg(feature = "xz_support")]
+    Xz(xz2::read::XzDecoder<R>),
+
+    #[cfg(feature = "xz_support")]
+    Lzma2Raw(codecs::lzma::RawDecoder<R>),
+
+    Buffered(io::Cursor<Vec<u8>>),
+}
+
+impl<R: Read> Decoder<R> {
+    /// Creates a decoder that reads data encoded with `codec` from `reader`.
+    pub fn new(reader: R, codec: Codec) -> crate::Result<Self> {
+        let inner = match codec {
+            Codec::Identity => DecoderInner::Identity(reader),
+
+            #[cfg(feature = "gzip_support")]
+            Codec::Gzip => DecoderInner::Gzip(codecs::gzip::decoder(reader)?),
+
+            #[cfg(feature = "deflate_support")]
+            Codec::Deflate => DecoderInner::Deflate(codecs::deflate::decoder(reader)?),
+
+            #[cfg(feature = "zlib_support")]
+            Codec::Zlib => DecoderInner::Zlib(codecs::zlib::decoder(reader)?),
+
+            #[cfg(feature = "zstd_support")]
+            Codec::Zstd => DecoderInner::Zstd(codecs::zstd::decoder(reader)?),
+
+            #[cfg(feature = "brotli_support")]
+            Codec::Brotli => DecoderInner::Brotli(codecs::brotli::decoder(reader)?),
+
+            #[cfg(feature = "bzip2_support")]
+            Codec::Bzip2 => DecoderInner::Bzip2(codecs::bzip2::decoder(reader)?),
+
+            #[cfg(feature = "lz4_support")]
+            Codec::Lz4 => DecoderInner::Lz4(codecs::lz4::decoder(reader)?),
+
+            #[cfg(feature = "lz4_support")]
+            Codec::Lz4Block => DecoderInner::Buffered(read_buffered(reader, codec)?),
+
+            #[cfg(feature = "snappy_support")]
+            Codec::Snappy => DecoderInner::Snappy(codecs::snappy::decoder(reader)?),
+
+            #[cfg(feature = "snappy_support")]
+            Codec::SnappyRaw => DecoderInner::Buffered(read_buffered(reader, codec)?),
+
+            #[cfg(feature = "xz_support")]
+            Codec::Xz => DecoderInner::Xz(codecs::xz::decoder(reader)?),
+
+            #[cfg(feature = "xz_support")]
+            Codec::Lzma => DecoderInner::Xz(codecs::lzma::decoder(reader)?),
+
+            #[cfg(feature = "xz_support")]
+            Codec::Lzma2Raw => DecoderInner::Lzma2Raw(codecs::lzma::RawDecoder::new(
+                reader,
+                &crate::XzOptions::default(),
+            )?),
+
+            #[cfg(feature = "bincode_support")]
+            Codec::BinCode => DecoderInner::Buffered(read_buffered(reader, codec)?),
+
+            #[cfg(feature = "base58_support")]
+            Codec::Base58
+            | Codec::Base58Ripple
+            | Codec::Base58Flickr
+            | Codec::Base58Check
+            | Codec::Base58CheckRipple
+            | Codec::Base58CheckFlickr => DecoderInner::Buffered(read_buffered(reader, codec)?),
+
+            #[cfg(feature = "base64_support")]
+            Codec::Base64 | Codec::Base64Url | Codec::Base64NoPad | Codec::Base64UrlNoPad => {
+                DecoderInner::Buffered(read_buffered(reader, codec)?)
+            }
+
+            #[cfg(feature = "base32_support")]
+            Codec::Base32 | Codec::Base32Crockford => {
+                DecoderInner::Buffered(read_buffered(reader, codec)?)
+            }
+
+            #[cfg(feature = "hex_support")]
+            Codec::Hex => DecoderInner::Buffered(read_buffered(reader, codec)?),
+
+            Codec::Custom(_) => DecoderInner::Buffered(read_buffered(reader, codec)?),
+
+            disabled => return Err(Error::CodecDisabled(disabled)),
+        };
+
+        Ok(Decoder { inner })
+    }
+}
+
+fn read_buffered<R: Read>(mut reader: R, codec: Codec) -> crate::Result<io::Cursor<Vec<u8>>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    Ok(io::Cursor::new(crate::decode(&data, codec)?))
+}
+
+impl<R: Read> Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            DecoderInner::Identity(reader) => reader.read(buf),
+
+            #[cfg(feature = "gzip_support")]
+            DecoderInner::Gzip(decoder) => decoder.read(buf),
+
+            #[cfg(feature = "deflate_support")]
+            DecoderInner::Deflate(decoder) => decoder.read(buf),
+
+            #[cfg(feature = "zlib_support")]
+            DecoderInner::Zlib(decoder) => decoder.read(buf),
+
+            #[cfg(feature = "zstd_support")]
+            DecoderInner::Zstd(decoder) => decoder.read(buf),
+
+            #[cfg(feature = "brotli_support")]
+            DecoderInner::Brotli(decoder) => decoder.read(buf),
+
+            #[cfg(feature = "bzip2_support")]
+            DecoderInner::Bzip2(decoder) => decoder.read(buf),
+
+            #[cfg(feature = "lz4_support")]
+            DecoderInner::Lz4(decoder) => decoder.read(buf),
+
+            #[cfg(feature = "snappy_support")]
+            DecoderInner::Snappy(decoder) => decoder.read(buf),
+
+            #[cfg(feature = "xz_support")]
+            DecoderInner::Xz(decoder) => decoder.read(buf),
+
+            #[cfg(feature = "xz_support")]
+            DecoderInner::Lzma2Raw(decoder) => decoder.read(buf),
+
+            DecoderInner::Buffered(cursor) => cursor.read(buf),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::io::{Read, Write};
+
+    const TEST_DATA: &[u8] = include_bytes!("ipsum.txt");
+
+    #[test]
+    fn stream_encode_matches_decode() {
+        for &codec in ALL_CODECS.iter().filter(|&&codec| is_codec_enabled(codec)) {
+            let mut encoder = Encoder::new(Vec::new(), codec, Quality::Default).unwrap();
+            for chunk in TEST_DATA.chunks(1000) {
+                encoder.write_all(chunk).unwrap();
+            }
+            let encoded = encoder.finish().unwrap();
+            let decoded = decode(&encoded, codec).unwrap();
+            assert_eq!(decoded, TEST_DATA, "{}", codec);
+        }
+    }
+
+    #[test]
+    fn stream_decode_matches_encode() {
+        for &codec in ALL_CODECS.iter().filter(|&&codec| is_codec_enabled(codec)) {
+            let encoded = encode(TEST_DATA, codec, Quality::Default).unwrap();
+            let mut decoder = Decoder::new(encoded.as_slice(), codec).unwrap();
+            let mut decoded = Vec::new();
+            decoder.read_to_end(&mut decoded).unwrap();
+            assert_eq!(decoded, TEST_DATA, "{}", codec);
+        }
+    }
+}
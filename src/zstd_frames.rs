@@ -0,0 +1,239 @@
+use crate::{codecs, Codec, Error, Result};
+use std::io;
+
+const SKIPPABLE_MAGIC_MASK: u32 = 0xffff_fff0;
+const SKIPPABLE_HEADER_LEN: usize = 8;
+const CHECKSUM_FLAG: u8 = 0x04;
+
+/// The type of a frame in a zstd stream.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ZstdFrameKind {
+    /// A compressed frame, described by its frame header.
+    Data {
+        /// The decoded size, if the encoder recorded it.
+        content_size: Option<u64>,
+
+        /// The frame ends with a checksum of the decoded data.
+        checksum: bool,
+
+        /// The id of the dictionary needed to decode the frame, if any.
+        dictionary_id: Option<u32>,
+    },
+
+    /// A skippable frame, which decoders ignore, identified by the low 4 bits
+    /// of its magic number.
+    Skippable { variant: u8 },
+}
+
+/// The position and header of a frame in a zstd stream.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ZstdFrameInfo {
+    /// The offset of the frame in the stream.
+    pub offset: usize,
+
+    /// The length of the frame in bytes, including its header.
+    pub len: usize,
+
+    pub kind: ZstdFrameKind,
+}
+
+/// A skippable frame found while decoding a zstd stream.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ZstdSkippableFrame {
+    pub variant: u8,
+    pub payload: Vec<u8>,
+
+    /// The length of the data decoded before the frame, so that metadata can
+    /// be matched to the data that follows it.
+    pub position: usize,
+}
+
+/// An iterator over the frames of a zstd stream, returned by `zstd_frames`.
+#[derive(Clone, Debug)]
+pub struct ZstdFrames<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for ZstdFrames<'a> {
+    type Item = Result<ZstdFrameInfo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.data.len() {
+            return None;
+        }
+        let result = parse_frame(&self.data[self.offset..]).map(|(len, kind)| ZstdFrameInfo {
+            offset: self.offset,
+            len,
+            kind,
+        });
+        match &result {
+            Ok(info) => self.offset += info.len,
+            Err(_) => self.offset = self.data.len(),
+        }
+        Some(result)
+    }
+}
+
+/// Iterates over the compressed and skippable frames of a zstd stream,
+/// reading only the frame headers.
+pub fn zstd_frames(data: &[u8]) -> ZstdFrames<'_> {
+    ZstdFrames { data, offset: 0 }
+}
+
+/// Wraps `payload` in a skippable frame, which can be concatenated with
+/// compressed frames and is ignored by zstd decoders.
+///
+/// The `variant`, from 0 to 15, is stored in the frame's magic number.
+pub fn encode_zstd_skippable(payload: &[u8], variant: u8) -> Result<Vec<u8>> {
+    if variant > 15 {
+        return Err(invalid_input(
+            "skippable frame variant must be from 0 to 15",
+        ));
+    }
+    if payload.len() > u32::MAX as usize {
+        return Err(invalid_input(
+            "skippable frame payload is larger than 4 GiB",
+        ));
+    }
+    let mut buf = Vec::with_capacity(SKIPPABLE_HEADER_LEN + payload.len());
+    buf.extend_from_slice(&(zstd_safe::MAGIC_SKIPPABLE_START + u32::from(variant)).to_le_bytes());
+    buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    buf.extend_from_slice(payload);
+    Ok(buf)
+}
+
+/// Decodes every frame of a zstd stream, returning the decoded data and the
+/// skippable frames in the order they appear.
+pub fn decode_zstd_frames(data: &[u8]) -> Result<(Vec<u8>, Vec<ZstdSkippableFrame>)> {
+    let mut buf = Vec::new();
+    let mut skippable = Vec::new();
+    for frame in zstd_frames(data) {
+        let frame = frame?;
+        let bytes = &data[frame.offset..frame.offset + frame.len];
+        match frame.kind {
+            ZstdFrameKind::Data { .. } => buf.extend(codecs::zstd::decode(bytes)?),
+            ZstdFrameKind::Skippable { variant } => skippable.push(ZstdSkippableFrame {
+                variant,
+                payload: bytes[SKIPPABLE_HEADER_LEN..].to_vec(),
+                position: buf.len(),
+            }),
+        }
+    }
+    Ok((buf, skippable))
+}
+
+fn parse_frame(data: &[u8]) -> Result<(usize, ZstdFrameKind)> {
+    let magic = match data.get(..4) {
+        Some(magic) => u32::from_le_bytes([magic[0], magic[1], magic[2], magic[3]]),
+        None => return Err(corrupt("truncated frame header")),
+    };
+
+    if magic & SKIPPABLE_MAGIC_MASK == zstd_safe::MAGIC_SKIPPABLE_START {
+        let size = match data.get(4..SKIPPABLE_HEADER_LEN) {
+            Some(size) => u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as usize,
+            None => return Err(corrupt("truncated skippable frame header")),
+        };
+        if data.len() - SKIPPABLE_HEADER_LEN < size {
+            return Err(corrupt("truncated skippable frame"));
+        }
+        let variant = (magic & !SKIPPABLE_MAGIC_MASK) as u8;
+        return Ok((
+            SKIPPABLE_HEADER_LEN + size,
+            ZstdFrameKind::Skippable { variant },
+        ));
+    }
+
+    if magic != zstd_safe::MAGICNUMBER {
+        return Err(corrupt("unknown frame magic number"));
+    }
+    let len = zstd_safe::find_frame_compressed_size(data)
+        .map_err(|code| corrupt(zstd_safe::get_error_name(code)))?;
+    let content_size = match zstd_safe::get_frame_content_size(data) {
+        zstd_safe::CONTENTSIZE_ERROR => return Err(corrupt("invalid frame header")),
+        zstd_safe::CONTENTSIZE_UNKNOWN => None,
+        size => Some(size),
+    };
+    let dictionary_id = match zstd_safe::get_dict_id_from_frame(data) {
+        0 => None,
+        id => Some(id),
+    };
+    Ok((
+        len,
+        ZstdFrameKind::Data {
+            content_size,
+            checksum: data[4] & CHECKSUM_FLAG != 0,
+            dictionary_id,
+        },
+    ))
+}
+
+fn corrupt(details: &str) -> Error {
+    Error::corrupt(Codec::Zstd, details)
+}
+
+fn invalid_input(details: &str) -> Error {
+    Error::Io(io::Error::new(io::ErrorKind::InvalidInput, details))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    const TEST_DATA: &[u8] = include_bytes!("ipsum.txt");
+
+    #[test]
+    fn zstd_frames_skippable() {
+        let (first, second) = TEST_DATA.split_at(1000);
+        let mut encoded = encode(first, Codec::Zstd, Quality::Default).unwrap();
+        let first_len = encoded.len();
+        encoded.extend(encode_zstd_skippable(b"metadata", 3).unwrap());
+        let options = ZstdOptions::new(Quality::Level1).checksum(true);
+        encoded.extend(encode_with_options(second, Codec::Zstd, options).unwrap());
+
+        let frames: Vec<ZstdFrameInfo> = zstd_frames(&encoded).map(|f| f.unwrap()).collect();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].offset, 0);
+        assert_eq!(frames[0].len, first_len);
+        assert!(matches!(
+            frames[0].kind,
+            ZstdFrameKind::Data {
+                checksum: false,
+                dictionary_id: None,
+                ..
+            }
+        ));
+        assert_eq!(frames[1].offset, first_len);
+        assert_eq!(frames[1].len, 16);
+        assert_eq!(frames[1].kind, ZstdFrameKind::Skippable { variant: 3 });
+        assert!(matches!(
+            frames[2].kind,
+            ZstdFrameKind::Data { checksum: true, .. }
+        ));
+        assert_eq!(frames[2].offset + frames[2].len, encoded.len());
+
+        assert_eq!(decode(&encoded, Codec::Zstd).unwrap(), TEST_DATA);
+        let (decoded, skippable) = decode_zstd_frames(&encoded).unwrap();
+        assert_eq!(decoded, TEST_DATA);
+        assert_eq!(
+            skippable,
+            vec![ZstdSkippableFrame {
+                variant: 3,
+                payload: b"metadata".to_vec(),
+                position: 1000,
+            }]
+        );
+    }
+
+    #[test]
+    fn zstd_frames_invalid() {
+        assert!(encode_zstd_skippable(b"", 16).is_err());
+        let encoded = encode(TEST_DATA, Codec::Zstd, Quality::Default).unwrap();
+        let mut frames = zstd_frames(&encoded[..encoded.len() - 1]);
+        assert!(frames.next().unwrap().is_err());
+        assert!(frames.next().is_none());
+        assert!(decode_zstd_frames(b"not zstd").is_err());
+        let skippable = encode_zstd_skippable(b"metadata", 0).unwrap();
+        assert!(decode_zstd_frames(&skippable[..10]).is_err());
+    }
+}